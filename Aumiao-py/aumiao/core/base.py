@@ -3,7 +3,7 @@ from typing import Any
 
 from aumiao.api import auth, community, edu, forum, library, shop, user, whale, work
 from aumiao.utils.acquire import ClientFactory, CodeMaoClient
-from aumiao.utils.data import CacheManager, CodeMaoFile, DataManager, HistoryManager, NestedDefaultDict, PathConfig, SettingManager
+from aumiao.utils.data import CacheHistoryManager, CacheManager, CodeMaoFile, DataManager, HistoryManager, NestedDefaultDict, PathConfig, SettingManager
 from aumiao.utils.decorator import singleton
 from aumiao.utils.tool import OutputHandler, ToolKitFactory
 
@@ -57,12 +57,16 @@ class CoreManager:
 
 	def __init__(self) -> None:
 		# 立即初始化的核心组件
-		self.client = ClientFactory().create_codemao_client()
 		self.toolkit = ToolKitFactory()
 		self.data_manager = DataManager()
 		self.path_config = PathConfig()
 		self.setting_manager = SettingManager()
 
+	@property
+	def client(self) -> CodeMaoClient:
+		"""客户端在首次使用时创建, 以便启动时先完成配置校验, 避免配置错误表现为客户端初始化失败"""
+		return ClientFactory().create_codemao_client()
+
 
 # ==============================
 # 基础设施协调器: 类型友好主类
@@ -104,6 +108,7 @@ class InfrastructureCoordinator:
 			"whale_motion": whale.ReportHandler,
 			"whale_obtain": whale.ReportFetcher,
 			"cache_manager": CacheManager,
+			"cache_history_manager": CacheHistoryManager,
 			"history_manager": HistoryManager,
 			"nested_defaultdict": NestedDefaultDict,
 			"file_manager": CodeMaoFile,
@@ -234,6 +239,11 @@ class InfrastructureCoordinator:
 		"""缓存"""
 		return self._modules.get("cache_manager")
 
+	@property
+	def cache_history_manager(self) -> "CacheHistoryManager":
+		"""缓存快照历史"""
+		return self._modules.get("cache_history_manager")
+
 	@property
 	def history_manager(self) -> "HistoryManager":
 		"""上传历史"""
@@ -354,5 +364,6 @@ class Tool:
 				},
 				timestamp_field="timestamp",
 			)
-		# 更新缓存
+		# 更新缓存并记录快照历史
 		coordinator.cache_manager.update(user_data)
+		coordinator.cache_history_manager.append(coordinator.cache_manager.data)
@@ -21,7 +21,7 @@ from aumiao.core.models import (
 )
 from aumiao.core.retrieve import Obtain
 from aumiao.utils.acquire import FileUploader, HTTPStatus
-from aumiao.utils.data import UploadHistory
+from aumiao.utils.data import Answer, CommentTemplate, UploadHistory, find_answer, pick_template
 from aumiao.utils.decorator import singleton
 
 
@@ -188,7 +188,8 @@ class BlacklistProcessStrategy(AbnormalProcessStrategy):
 		user_id = str(data.get("user_id", ""))
 		blacklist_set = params.get("blacklist", set())
 		if isinstance(blacklist_set, list):
-			blacklist_set = set(blacklist_set)
+			# 兼容直接传入黑名单条目或用户 ID 列表
+			blacklist_set = {getattr(entry, "user_id", entry) for entry in blacklist_set}
 		return user_id in blacklist_set
 
 	def _format_log_message(self, data: dict[str, Any], log_type: str, source_type: str, title: str, parent_info: str) -> str:  # noqa: PLR6301
@@ -756,9 +757,9 @@ class ViolationChecker:
 				limit=limit,
 			)
 			# 2. 违规检查参数
-			check_params: dict[Literal["ads", "blacklist", "duplicates"], list[str] | int] = {
+			check_params: dict[Literal["ads", "blacklist", "duplicates"], list[str] | set[str] | int] = {
 				"ads": coordinator.data_manager.data.USER_DATA.ads,
-				"blacklist": coordinator.data_manager.data.USER_DATA.black_room,
+				"blacklist": coordinator.data_manager.blacklisted_ids(),
 				"duplicates": coordinator.setting_manager.data.PARAMETER.spam_del_max,
 			}
 
@@ -1079,22 +1080,22 @@ class ReplyProcessor:
 			found = coordinator.toolkit.create_string_processor().find_substrings(
 				text=target_id_str,
 				candidates=comment_ids,
-			)[0]
+			).major
 			target_id = int(found) if found else 0
 		return target_id, parent_id
 
 	@staticmethod
-	def match_keyword(comment_text: str, formatted_answers: dict, formatted_replies: list) -> tuple:
-		"""匹配关键词"""
+	def match_keyword(comment_text: str, formatted_answers: list[Answer], formatted_replies: list[CommentTemplate]) -> tuple:
+		"""匹配关键词, 未命中时按权重随机选择一条通用回复"""
 		chosen = ""
 		matched_keyword = None
-		for keyword, resp in formatted_answers.items():
-			if keyword in comment_text:
-				matched_keyword = keyword
-				chosen = choice(resp) if isinstance(resp, list) else resp
-				break
+		answer = find_answer(formatted_answers, comment_text)
+		if answer is not None and answer.responses:
+			matched_keyword = answer.question
+			chosen = choice(answer.responses)
 		if not chosen:
-			chosen = choice(formatted_replies)
+			template = pick_template(formatted_replies)
+			chosen = template.text if template else ""
 		return chosen, matched_keyword
 
 	@staticmethod
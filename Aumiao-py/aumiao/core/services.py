@@ -1,5 +1,6 @@
 from collections import defaultdict
 from collections.abc import Callable, Generator
+from dataclasses import replace
 from pathlib import Path
 from time import sleep
 from typing import Any, Literal, cast
@@ -10,7 +11,8 @@ from aumiao.core.models import VALID_REPLY_TYPES, SourceConfigSimple
 from aumiao.core.process import CommentProcessor, FileProcessor, MultiAccount, ReplyProcessor, ReportFetcher, ReportProcessor
 from aumiao.core.retrieve import Obtain
 from aumiao.utils.acquire import CodeMaoClient, HTTPStatus
-from aumiao.utils.decorator import singleton, skip_on_error
+from aumiao.utils.data import Answer, CommentTemplate
+from aumiao.utils.decorator import ErrorSink, singleton
 
 
 # ==============================
@@ -141,6 +143,7 @@ class ReplyService:
 			try:
 				if self._process_single_reply(reply, formatted_answers, formatted_replies):
 					processed_count += 1
+					coordinator.cache_manager.increment("replies_sent")
 					sleep(5)  # 防止请求过快
 			except Exception as e:
 				print(f"处理通知时发生错误: {e!s}")
@@ -151,37 +154,12 @@ class ReplyService:
 	def _get_formatted_replies() -> dict:
 		"""获取格式化的回复内容"""
 		coordinator_data = coordinator.data_manager
-		formatted_answers = {}
-		# 格式化答案
-		for answer in coordinator_data.data.USER_DATA.answers:
-			for keyword, resp in answer.items():
-				if isinstance(resp, str):
-					try:
-						formatted_answers[keyword] = resp.format(**coordinator_data.data.INFO)
-					except (KeyError, ValueError):
-						formatted_answers[keyword] = resp
-				elif isinstance(resp, list):
-					# 处理列表中的每个字符串
-					formatted_resp = []
-					for item in resp:
-						if isinstance(item, str):
-							try:
-								formatted_resp.append(item.format(**coordinator_data.data.INFO))
-							except (KeyError, ValueError):
-								formatted_resp.append(item)
-						else:
-							formatted_resp.append(item)
-					formatted_answers[keyword] = formatted_resp
-		# 格式化回复
-		formatted_replies = []
-		for reply in coordinator_data.data.USER_DATA.replies:
-			if isinstance(reply, str):
-				try:
-					formatted_replies.append(reply.format(**coordinator_data.data.INFO))
-				except (KeyError, ValueError):
-					formatted_replies.append(reply)
-			else:
-				formatted_replies.append(reply)
+		render = coordinator.toolkit.create_data_converter().template_render
+		info = coordinator_data.data.INFO
+		# 格式化答案, 只替换回复内容, 匹配方式等字段保持不变
+		formatted_answers = [replace(answer, responses=[render(item, info) for item in answer.responses]) for answer in coordinator_data.data.USER_DATA.answers]
+		# 格式化回复, 保留权重等元数据供随机选择使用
+		formatted_replies = [replace(reply, text=render(reply, info)) for reply in coordinator_data.data.USER_DATA.replies]
 		return {"answers": formatted_answers, "replies": formatted_replies}
 
 	@staticmethod
@@ -194,7 +172,7 @@ class ReplyService:
 		)
 		return new_replies or []
 
-	def _process_single_reply(self, reply: dict, formatted_answers: dict, formatted_replies: list) -> bool:
+	def _process_single_reply(self, reply: dict, formatted_answers: list[Answer], formatted_replies: list[CommentTemplate]) -> bool:
 		"""处理单个回复"""
 		# 基础信息提取
 		reply_id = reply.get("id", "")
@@ -314,7 +292,7 @@ class CommunityService:
 		config: SourceConfigSimple = cast("SourceConfigSimple", self.source_config[source])
 		params: dict[Literal["ads", "blacklist", "duplicates"], Any] = {
 			"ads": coordinator.data_manager.data.USER_DATA.ads,
-			"blacklist": coordinator.data_manager.data.USER_DATA.black_room,
+			"blacklist": coordinator.data_manager.blacklisted_ids(),
 			"duplicates": coordinator.setting_manager.data.PARAMETER.spam_del_max,
 		}
 		target_lists = defaultdict(list)
@@ -332,7 +310,6 @@ class CommunityService:
 		}
 
 	@staticmethod
-	@skip_on_error
 	def _execute_comment_deletion(target_list: list, delete_handler: Callable[[int, int, bool], bool], label: str) -> dict:
 		"""执行删除操作"""
 		if not target_list:
@@ -346,17 +323,28 @@ class CommunityService:
 			return {"success": False, "deleted_count": 0, "details": []}
 		deleted_count = 0
 		details = []
-		for entry in reversed(target_list):
+		error_sink = ErrorSink()
+
+		def delete_entry(entry: str) -> bool:
 			parts = entry.split(":")[0].split(".")
 			item_id, comment_id = map(int, parts)
 			is_reply = ":reply" in entry
-			if not delete_handler(item_id, comment_id, is_reply):
+			return delete_handler(item_id, comment_id, is_reply)
+
+		for entry in reversed(target_list):
+			deleted = error_sink.call(entry, delete_entry, entry)
+			if deleted is None:
+				details.append({"entry": entry, "status": "error"})
+			elif not deleted:
 				print(f"删除失败: {entry}")
 				details.append({"entry": entry, "status": "failed"})
 			else:
 				print(f"已删除: {entry}")
 				deleted_count += 1
+				coordinator.cache_manager.increment("comments_deleted")
 				details.append({"entry": entry, "status": "success"})
+		if error_sink:
+			print(f"{len(error_sink)} 条 {label} 处理出错: {error_sink.summary()}")
 		return {"success": True, "deleted_count": deleted_count, "details": details}
 
 	@staticmethod
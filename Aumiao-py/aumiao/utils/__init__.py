@@ -11,13 +11,20 @@ _is_compiling: bool = hasattr(sys, "_nuitka_compiled") or hasattr(sys, "frozen")
 
 # 类型检查支持
 if TYPE_CHECKING or _is_compiling:
-	from . import acquire, data, decorator, tool
+	from . import acquire, data, decorator, diff, schema, tool
 
 # 模块路径映射
-_MODULE_PATHS: Final[dict[str, str]] = {"acquire": ".utils.acquire", "data": ".utils.data", "decorator": ".utils.decorator", "tool": ".utils.tool"}
+_MODULE_PATHS: Final[dict[str, str]] = {
+	"acquire": ".utils.acquire",
+	"data": ".utils.data",
+	"decorator": ".utils.decorator",
+	"diff": ".utils.diff",
+	"schema": ".utils.schema",
+	"tool": ".utils.tool",
+}
 
 # 固定的导出列表
-__all__: Final[tuple[str, ...]] = ("__version__", "acquire", "data", "decorator", "tool")
+__all__: Final[tuple[str, ...]] = ("__version__", "acquire", "data", "decorator", "diff", "schema", "tool")
 
 # 模块缓存
 _LOADED_MODULES: dict[str, ModuleType] = {}
@@ -50,4 +57,4 @@ def __dir__() -> list[str]:
 # 编译时确保所有模块都被引用
 if _is_compiling:
 	# 这些引用确保编译时包含所有模块
-	_ = acquire, data, decorator, tool
+	_ = acquire, data, decorator, diff, schema, tool
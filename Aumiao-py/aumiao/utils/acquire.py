@@ -1,23 +1,55 @@
 from abc import ABC, abstractmethod
-from collections.abc import Generator
+from collections.abc import Callable, Generator
 from dataclasses import dataclass, field
 from enum import Enum
+from mimetypes import guess_type
 from pathlib import Path
-from random import choice
-from time import sleep
+from random import Random, choice
+from re import IGNORECASE
+from re import compile as re_compile
+from time import perf_counter, sleep
 from types import TracebackType
 from typing import Any, Literal, Self, TypedDict
 
-from httpx import Client, ConnectError, HTTPStatusError, Response, TimeoutException
+from httpx import Client, ConnectError, Headers, HTTPError, HTTPStatusError, Request, Response, TimeoutException
 
 from aumiao.utils import tool
-from aumiao.utils.data import CodeMaoFile, SettingManager
-from aumiao.utils.decorator import singleton
+from aumiao.utils.data import HEADER_NAME_PATTERN, HEADER_VALUE_PATTERN, BaseManager, CodeMaoFile, CodeMaoSetting, LogLevel, Parameter, PathConfig, SettingManager
+from aumiao.utils.decorator import RetryBudget, TimingRegistry, singleton_fallible
 
 setting_manager = SettingManager()
+# 内置默认请求头, 保证 setting.json 缺失请求头时客户端仍可正常工作
+DEFAULT_HEADERS: dict[str, str] = {
+	"Accept": "application/json, text/plain, */*",
+	"Content-Type": "application/json;charset=UTF-8",
+	"User-Agent": "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/141.0.0.0 Safari/537.36 Edg/141.0.0.0",
+}
+# 路径中的 ID 段 (纯数字、长十六进制串或 UUID), 统计耗时时替换为 {id} 以免每个 ID 单独占一行
+ROUTE_ID_PATTERN = re_compile(r"/(?:\d+|[0-9a-f]{16,}|[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12})(?=/|$)", IGNORECASE)
+# 安全模式下禁止发送的请求方法
+MUTATING_METHODS = frozenset({"DELETE", "PATCH", "POST", "PUT"})
+
+
+class SafeModeError(PermissionError):
+	"""安全模式下尝试发送修改类请求"""
+
+
+class RetryBudgetExhausted(RuntimeError):
+	"""重试预算已耗尽, 放弃重试; 最后一次失败的异常可通过 __cause__ 获取"""
 
 
 # ==================== 配置类 ====================
+@dataclass
+class RetryEvent:
+	"""重试事件, 在每次重试等待前发出"""
+
+	attempt: int = 0
+	delay: float = 0.0
+	error: str = ""
+	exception: Exception | None = None
+	max_attempts: int = 0
+
+
 @dataclass
 class ClientConfig:
 	"""客户端配置 - 重构版本"""
@@ -37,7 +69,60 @@ class ClientConfig:
 	timeout: float = 30.0
 	max_retries: int = 3
 	retry_delay: float = 1.0
+	# 重试间隔策略: exponential 为 retry_delay * 2^n * backoff_factor, fixed 为固定 retry_delay
+	retry_backoff: Literal["exponential", "fixed"] = "exponential"
+	# 随机抖动比例 (0~1), 实际间隔在 [delay * (1 - jitter), delay] 之间
+	retry_jitter: float = 0.0
+	# 单次重试间隔上限, None 表示不限制
+	retry_max_delay: float | None = None
+	# 判断异常是否值得重试, 返回 False 时立即结束 (例如 404 等不会因重试而成功的错误)
+	retry_if: Callable[[Exception], bool] | None = None
+	# 重试预算, 耗尽后不再重试而是抛出 RetryBudgetExhausted; 可在访问同一主机的客户端间共享
+	retry_budget: RetryBudget | None = None
+	# 每次重试等待前调用, 可用于显示进度或统计失败率, 回调内的异常只记录不中断重试
+	on_retry: Callable[[RetryEvent], None] | None = None
 	log_requests: bool = True
+	# 全局日志级别, None 时由 log_requests 决定
+	log_level: LogLevel | None = None
+	# 按 URL 前缀覆盖日志级别
+	endpoint_log_overrides: dict[str, LogLevel] = field(default_factory=dict)
+	# 演练模式: 只记录请求, 不发送
+	dry_run: bool = False
+
+	def __post_init__(self) -> None:
+		if not 0 <= self.retry_jitter <= 1:
+			msg = f"retry_jitter 必须位于 [0, 1] 区间, 实际为 {self.retry_jitter}"
+			raise ValueError(msg)
+		if self.retry_max_delay is not None and self.retry_max_delay < self.retry_delay:
+			msg = f"retry_max_delay ({self.retry_max_delay}) 不能小于 retry_delay ({self.retry_delay})"
+			raise ValueError(msg)
+
+	def retry_delays(self, retries: int, backoff_factor: float = 1.0, rng: Random | None = None) -> Generator[float]:
+		"""依次生成每次重试前的等待秒数, 共 retries - 1 个, 便于在不休眠的情况下验证"""
+		rng = rng or Random()
+		for attempt in range(max(retries - 1, 0)):
+			delay = self.retry_delay * (2**attempt * backoff_factor) if self.retry_backoff == "exponential" else self.retry_delay
+			if self.retry_max_delay is not None:
+				delay = min(delay, self.retry_max_delay)
+			yield delay * (1 - self.retry_jitter * rng.random())
+
+	def log_level_for(self, url: str) -> LogLevel:
+		"""获取 URL 对应的日志级别, 匹配多个前缀时取最长者"""
+		matched = [prefix for prefix in self.endpoint_log_overrides if url.startswith(prefix) or url.startswith(self.get_base_url() + prefix)]
+		if matched:
+			return self.endpoint_log_overrides[max(matched, key=len)]
+		if self.log_level is not None:
+			return self.log_level
+		return "all" if self.log_requests else "none"
+
+	@classmethod
+	def log_settings(cls, parameter: Parameter) -> dict[str, Any]:
+		"""从配置项中提取日志相关的客户端配置"""
+		return {
+			"endpoint_log_overrides": dict(parameter.endpoint_log_overrides),
+			"log_level": parameter.log_level or None,
+			"log_requests": parameter.log,
+		}
 
 	def get_base_url(self, key: str | None = None) -> str:
 		"""获取指定 key 的基础 URL"""
@@ -48,12 +133,39 @@ class ClientConfig:
 class HTTPStatus(Enum):
 	"""HTTP 状态码枚举"""
 
+	BAD_GATEWAY = 502
+	BAD_REQUEST = 400
+	CONFLICT = 409
 	CREATED = 201
 	FORBIDDEN = 403
+	GATEWAY_TIMEOUT = 504
+	INTERNAL_SERVER_ERROR = 500
 	NOT_FOUND = 404
 	NOT_MODIFIED = 304
 	NO_CONTENT = 204
 	OK = 200
+	SERVICE_UNAVAILABLE = 503
+	TOO_MANY_REQUESTS = 429
+	UNAUTHORIZED = 401
+	UNPROCESSABLE_ENTITY = 422
+
+	@classmethod
+	def from_code(cls, code: int) -> Self | None:
+		"""根据状态码获取枚举成员, 未知状态码返回 None"""
+		try:
+			return cls(code)
+		except ValueError:
+			return None
+
+
+@dataclass
+class HealthStatus:
+	"""API 连通性检查结果"""
+
+	latency_ms: int = 0
+	reachable: bool = False
+	status_code: int = 0
+	timestamp: int = 0
 
 
 class PaginationConfig(TypedDict, total=False):
@@ -100,6 +212,8 @@ class IHTTPClient(ABC):
 		pagination_method: Literal["offset", "page"] = "offset",
 		config: PaginationConfig | None = None,
 		base_url_key: str | None = None,
+		*,
+		fallback_to_empty_page: bool = False,
 	) -> Generator[dict[str, Any]]:
 		"""获取分页数据"""
 
@@ -218,10 +332,10 @@ class BaseHTTPClient:
 
 	def __init__(self, config: ClientConfig) -> None:
 		self.config = config
-		self.headers = setting_manager.data.PROGRAM.HEADERS.copy()
+		self.headers = self.build_headers(setting_manager.data.PROGRAM.HEADERS)
 		self._http_client = Client(headers=self.headers, timeout=config.timeout)
 		self._data_processor = tool.DataProcessor()
-		self.log_file = Path.cwd() / "logs" / f"requests_{tool.TimeUtils().current_timestamp()}.txt"
+		self.log_file = PathConfig.LOG_DIR / f"requests_{tool.TimeUtils().current_timestamp()}.txt"
 		self._pagination_config: PaginationConfig = {
 			"offset_key": "offset",
 			"amount_key": "limit",
@@ -246,15 +360,14 @@ class BaseHTTPClient:
 		base_url_key: Literal["default", "creation", "edu", "whale"] | None = None,
 	) -> Response:
 		"""统一的 HTTP 请求方法 - 添加 base_url_key 参数"""
-		# 构建完整的 URL
-		if endpoint.startswith("http"):
-			url = endpoint
-		else:
-			base_url = self.config.get_base_url(base_url_key)
-			url = f"{base_url}{endpoint}"
+		url = self._build_url(endpoint, base_url_key)
 		retries = retries or self.config.max_retries
 		timeout = timeout or self.config.timeout
-		log_enabled = bool(self.config.log_requests and log)
+		log_level = self.config.log_level_for(url) if log else "none"
+		self._check_safe_mode(method, url)
+		if self.config.dry_run:
+			return self._dry_run_response(method, url, params=params, data=data, payload=payload, files=files, headers=self._prepare_headers(headers, files))
+		delays = list(self.config.retry_delays(retries, backoff_factor))
 		for attempt in range(retries):
 			try:
 				request_headers = self._prepare_headers(headers, files)
@@ -268,35 +381,94 @@ class BaseHTTPClient:
 				# print("Payload:", payload)
 				# print("Files:", files)
 				# print("&" * 50)
-				response = self._execute_request(
-					method=method,
-					url=url,
-					params=params,
-					data=data,
-					payload=payload,
-					files=files,
-					headers=request_headers,
-					timeout=timeout,
-				)
-				if log_enabled:
+				with TimingRegistry().measure(f"request {method.upper()} {self.route_template(endpoint)}"):
+					response = self._execute_request(
+						method=method,
+						url=url,
+						params=params,
+						data=data,
+						payload=payload,
+						files=files,
+						headers=request_headers,
+						timeout=timeout,
+					)
+				if log_level == "all" or (log_level == "errors" and response.is_error):
 					self._log_request(response)
 				response.raise_for_status()
 			except HTTPStatusError as e:
-				if attempt == retries - 1:
+				if attempt == retries - 1 or not self._should_retry(e):
 					return e.response
-				self._handle_retry(e, attempt)
+				self._withdraw_retry_budget(e)
+				self._handle_retry(e, attempt, retries, delays[attempt])
 			except (ConnectError, TimeoutException) as e:
-				if attempt == retries - 1:
+				if attempt == retries - 1 or not self._should_retry(e):
 					raise
-				self._handle_retry(e, attempt)
+				self._withdraw_retry_budget(e)
+				self._handle_retry(e, attempt, retries, delays[attempt])
 			except Exception as e:
 				print(f"请求失败: {e}")
 				break
 			else:
+				if self.config.retry_budget is not None:
+					self.config.retry_budget.record_success()
 				return response
-			sleep(self.config.retry_delay * (2**attempt * backoff_factor))
+			sleep(delays[attempt])
 		return Response(500)
 
+	def head(
+		self,
+		endpoint: str,
+		params: dict[str, Any] | None = None,
+		*,
+		base_url_key: Literal["default", "creation", "edu", "whale"] | None = None,
+	) -> Headers:
+		"""发送 HEAD 请求并返回响应头, 可在下载前检查 Content-Length 或 Content-Type; 非 2xx 响应抛出 HTTPStatusError"""
+		response = self.send_request(method="HEAD", endpoint=endpoint, params=params, base_url_key=base_url_key)
+		if not response.is_success:
+			url = self._build_url(endpoint, base_url_key)
+			msg = f"HEAD 请求失败: {url} 状态码 {response.status_code}"
+			raise HTTPStatusError(msg, request=Request("HEAD", url, params=params), response=response)
+		return response.headers
+
+	def _build_url(self, endpoint: str, base_url_key: str | None) -> str:
+		"""构建完整的 URL, 以 http 开头的 endpoint 原样返回"""
+		if endpoint.startswith("http"):
+			return endpoint
+		return f"{self.config.get_base_url(base_url_key)}{endpoint}"
+
+	def _check_safe_mode(self, method: str, url: str) -> None:
+		"""安全模式下拒绝修改类请求并记录日志"""
+		if method.upper() not in MUTATING_METHODS or not BaseManager.is_safe_mode():
+			return
+		print(f"[safe_mode] 已拦截 {method.upper()} {url}")
+		CodeMaoFile().file_write(path=self.log_file, content=f"[{tool.TimeUtils().format_timestamp()}] safe_mode: blocked {method.upper()} {url}\n", method="a")
+		msg = f"安全模式下禁止发送 {method.upper()} 请求: {url}"
+		raise SafeModeError(msg)
+
+	@staticmethod
+	def route_template(endpoint: str) -> str:
+		"""去掉查询参数并将路径中的 ID 段替换为 {id}, 如 /creation-tools/v1/works/123 -> /creation-tools/v1/works/{id}"""
+		return ROUTE_ID_PATTERN.sub("/{id}", endpoint.split("?", 1)[0])
+
+	@staticmethod
+	def build_headers(user_headers: dict[str, str]) -> dict[str, str]:
+		"""
+		合并请求头: 先取内置默认请求头, 再用配置中的请求头覆盖 (配置优先)
+		配置中的值支持 ${VAR} 形式引用环境变量
+		名称或值不合法时抛出 ValueError, 并指明出错的请求头
+		"""
+		string_processor = tool.StringProcessor()
+		interpolated = {name: string_processor.interpolate_env_vars(value) if isinstance(value, str) else value for name, value in user_headers.items()}
+		headers = {**DEFAULT_HEADERS, **interpolated}
+		for name, value in headers.items():
+			if not HEADER_NAME_PATTERN.match(name):
+				msg = f"请求头名称 {name!r} 不是合法的 HTTP token"
+				raise ValueError(msg)
+			if not isinstance(value, str) or not HEADER_VALUE_PATTERN.match(value):
+				msg = f"请求头 {name!r} 的值包含换行、控制字符或非 ASCII 字符"
+				raise ValueError(msg)
+		return headers
+
 	def _prepare_headers(self, headers: dict[str, str] | None, files: dict[str, Any] | None) -> dict[str, str]:
 		"""准备请求头 - 修复版本"""
 		# 合并基础头和新头
@@ -331,10 +503,101 @@ class BaseHTTPClient:
 			request_args["json"] = payload
 		return self._http_client.request(**request_args)
 
-	@staticmethod
-	def _handle_retry(error: Exception, attempt: int) -> None:
+	def _dry_run_response(
+		self,
+		method: str,
+		url: str,
+		params: dict[str, Any] | None,
+		data: dict[str, Any] | None,
+		payload: dict[str, Any] | None,
+		files: dict[str, Any] | None,
+		headers: dict[str, str],
+	) -> Response:
+		"""演练模式下记录请求详情并返回空的 200 响应"""
+		# 隐藏认证信息, 避免凭据写入日志
+		headers = {k: "******" if k.lower() in {"authorization", "cookie"} else v for k, v in headers.items()}
+		log_entry = (
+			f"[{tool.TimeUtils().format_timestamp()}] dry_run: true\n"
+			f"Profile: {setting_manager.active_profile()}\n"
+			f"Method: {method.upper()}\n"
+			f"URL: {url}\n"
+			f"Headers: {headers}\n"
+			f"Params: {params}\n"
+			f"Data: {data}\n"
+			f"Payload: {payload}\n"
+			f"Files: {list(files) if files else None}\n"
+			f"{'=' * 50}\n\n"
+		)
+		print(f"[dry_run] {method.upper()} {url}")
+		CodeMaoFile().file_write(path=self.log_file, content=log_entry, method="a")
+		return Response(HTTPStatus.OK.value, json={}, request=Request(method.upper(), url))
+
+	def _should_retry(self, error: Exception) -> bool:
+		"""根据 retry_if 判断是否继续重试, 未配置时总是重试"""
+		return self.config.retry_if is None or self.config.retry_if(error)
+
+	def _withdraw_retry_budget(self, error: Exception) -> None:
+		"""从重试预算中取出一个许可, 预算不足时抛出 RetryBudgetExhausted; 未配置预算时总是允许"""
+		if self.config.retry_budget is None or self.config.retry_budget.try_withdraw():
+			return
+		msg = f"重试预算已耗尽, 放弃重试: {error}"
+		raise RetryBudgetExhausted(msg) from error
+
+	def _handle_retry(self, error: Exception, attempt: int, max_attempts: int, delay: float) -> None:
 		"""处理重试逻辑"""
 		print(f"请求失败, 第 {attempt + 1} 次重试: {error}")
+		if self.config.on_retry is None:
+			return
+		event = RetryEvent(attempt=attempt + 1, delay=delay, error=str(error), exception=error, max_attempts=max_attempts)
+		try:
+			self.config.on_retry(event)
+		except Exception as e:
+			print(f"on_retry 回调出错: {e}")
+
+	def health_check(self, endpoint: str = "/coconut/clouddb/currentTime", timeout: float = 2.0) -> HealthStatus:
+		"""请求轻量只读接口, 检查 API 连通性并测量往返耗时; 任何传输层错误都视为不可达, 不会抛出"""
+		url = self._build_url(endpoint, None)
+		timestamp = tool.TimeUtils().current_timestamp()
+		start = perf_counter()
+		try:
+			response = self._http_client.request("GET", url, timeout=timeout)
+		except HTTPError as e:
+			print(f"连通性检查失败: {e}")
+			return HealthStatus(latency_ms=int((perf_counter() - start) * 1000), reachable=False, timestamp=timestamp)
+		return HealthStatus(
+			latency_ms=int((perf_counter() - start) * 1000),
+			reachable=True,
+			status_code=response.status_code,
+			timestamp=timestamp,
+		)
+
+	def get(self, endpoint: str, params: dict[str, Any] | None = None) -> Response:
+		"""发送 GET 请求"""
+		return self.send_request("GET", endpoint, params=params)
+
+	def post(self, endpoint: str, payload: dict[str, Any] | None = None) -> Response:
+		"""发送 POST 请求"""
+		return self.send_request("POST", endpoint, payload=payload)
+
+	def put(self, endpoint: str, payload: dict[str, Any] | None = None) -> Response:
+		"""发送 PUT 请求"""
+		return self.send_request("PUT", endpoint, payload=payload)
+
+	def delete(self, endpoint: str, params: dict[str, Any] | None = None) -> Response:
+		"""发送 DELETE 请求"""
+		return self.send_request("DELETE", endpoint, params=params)
+
+	def get_json(self, endpoint: str, params: dict[str, Any] | None = None) -> Any:
+		"""发送 GET 请求并解析 JSON 响应"""
+		response = self.get(endpoint, params=params)
+		response.raise_for_status()
+		return response.json()
+
+	def upload_file(self, endpoint: str, field_name: str, file_path: Path, data: dict[str, Any] | None = None) -> Response:
+		"""以 multipart/form-data 上传文件, 根据扩展名推断 MIME 类型"""
+		mime_type = guess_type(file_path.name)[0] or "application/octet-stream"
+		files = {field_name: (file_path.name, file_path.read_bytes(), mime_type)}
+		return self.send_request("POST", endpoint, data=data, files=files)
 
 	def update_headers(self, headers: dict[str, str]) -> None:
 		"""更新请求头 - 修复版本"""
@@ -518,6 +781,36 @@ class BaseHTTPClient:
 				if self._reached_limit(yielded_count, limit):
 					return
 
+	def _fetch_until_empty_page(
+		self,
+		endpoint: str,
+		base_params: dict[str, Any],
+		payload: dict[str, Any] | None,
+		method: FetchMethod,
+		data_key: str,
+		pagination_method: Literal["offset", "page"],
+		config: PaginationConfig,
+		items_per_page: int,
+		current_count: int,
+		limit: int | None,
+		base_url_key: Literal["default", "creation", "edu", "whale"] | None = None,
+	) -> Generator[dict[str, Any]]:
+		"""在总数未知时逐页获取, 直到某页没有数据"""
+		yielded_count = current_count
+		offset_key = config.get("offset_key", "")
+		page_idx = 1
+		while True:
+			page_params = self._build_page_params(base_params, offset_key, page_idx, items_per_page, 0, pagination_method)
+			page_data = self._fetch_single_page(endpoint, method, page_params, payload, data_key, base_url_key)
+			if not page_data:
+				return
+			for item in page_data:
+				yield item
+				yielded_count += 1
+				if self._reached_limit(yielded_count, limit):
+					return
+			page_idx += 1
+
 	def fetch_paginated_data(
 		self,
 		endpoint: str,
@@ -530,9 +823,12 @@ class BaseHTTPClient:
 		pagination_method: Literal["offset", "page"] = "offset",
 		config: PaginationConfig | None = None,
 		base_url_key: Literal["default", "creation", "edu", "whale"] | None = None,
+		*,
+		fallback_to_empty_page: bool = False,
 	) -> Generator[dict[str, Any]]:
+		"""获取分页数据, fallback_to_empty_page 为 True 时若响应缺少总数则持续翻页直到空页"""
 		# 获取分页信息
-		total_items, items_per_page, first_page, _ = self._get_pagination_info(
+		total_items, items_per_page, first_page, response_data = self._get_pagination_info(
 			endpoint=endpoint,
 			params=params,
 			payload=payload,
@@ -552,6 +848,22 @@ class BaseHTTPClient:
 			yielded_count += 1
 			if self._reached_limit(yielded_count, limit):
 				return
+		# 响应中没有总数时, 改为翻页直到遇到空页
+		if fallback_to_empty_page and first_page and self._get_nested_value(response_data, total_key) is None:
+			yield from self._fetch_until_empty_page(
+				endpoint=endpoint,
+				base_params=base_params,
+				payload=payload,
+				method=method,
+				data_key=data_key,
+				pagination_method=pagination_method,
+				config=config_,
+				items_per_page=items_per_page,
+				current_count=yielded_count,
+				limit=limit,
+				base_url_key=base_url_key,
+			)
+			return
 		# 计算剩余需要获取的数据
 		remaining_to_fetch = self._calculate_remaining_items(total_items, len(first_page), limit, yielded_count)
 		if remaining_to_fetch <= 0:
@@ -610,6 +922,7 @@ class BaseHTTPClient:
 		"""记录请求日志"""
 		log_entry = (
 			f"[{tool.TimeUtils().format_timestamp()}]\n"
+			f"Profile: {setting_manager.active_profile()}\n"
 			f"Method: {response.request.method}\n"
 			f"URL: {response.url}\n"
 			f"Status: {response.status_code}\n"
@@ -630,23 +943,44 @@ class BaseHTTPClient:
 
 
 # ==================== 具体实现 ====================
-@singleton
+@singleton_fallible
 class CodeMaoClient(BaseHTTPClient):
 	"""编程猫 HTTP 客户端 - 修复版本"""
 
 	def __init__(self) -> None:
-		config = ClientConfig(log_requests=setting_manager.data.PARAMETER.log)
+		# 共享客户端默认启用重试预算, 接口降级时避免各处调用叠加重试
+		config = ClientConfig(dry_run=setting_manager.data.PARAMETER.dry_run, retry_budget=RetryBudget(), **ClientConfig.log_settings(setting_manager.data.PARAMETER))
 		super().__init__(config)
 		# 修复: 只创建一个 IdentityManager 实例
 		self.identity_manager = IdentityManager()
 		self.token = self.identity_manager.tokens  # 使用同一个实例
 		# 初始化时设置默认请求头
 		self._initialize_default_headers()
+		# 演练模式下不发送任何真实请求, 跳过连通性检查
+		if setting_manager.data.PARAMETER.check_connectivity_on_start and not self.config.dry_run:
+			self._warn_if_unreachable()
+		# 配置热重载后同步日志开关与请求头
+		setting_manager.add_reload_listener(self._apply_setting)
+
+	def _apply_setting(self, setting: CodeMaoSetting) -> None:
+		"""应用重新加载后的配置"""
+		for key, value in ClientConfig.log_settings(setting.PARAMETER).items():
+			setattr(self.config, key, value)
+		self.config.dry_run = setting.PARAMETER.dry_run
+		self.update_headers(self.build_headers(setting.PROGRAM.HEADERS))
+
+	def _warn_if_unreachable(self) -> None:
+		"""启动时检查 API 连通性, 不可达时仅给出警告"""
+		status = self.health_check()
+		if not status.reachable:
+			print("警告: 无法连接到编程猫 API, 请检查网络连接")
+		elif status.status_code >= HTTPStatus.INTERNAL_SERVER_ERROR.value:
+			print(f"警告: 编程猫 API 响应异常 (状态码 {status.status_code})")
 
 	def _initialize_default_headers(self) -> None:
 		"""初始化默认请求头"""
 		# 确保初始请求头正确设置
-		default_headers = setting_manager.data.PROGRAM.HEADERS.copy()
+		default_headers = self.build_headers(setting_manager.data.PROGRAM.HEADERS)
 		self.update_headers(default_headers)
 
 	def switch_identity(self, identity: str, token: str) -> None:
@@ -749,7 +1083,10 @@ class FileUploader(IFileUploader):
 
 	def _upload_request(self, method: str, endpoint: str, files: dict[str, Any] | None = None, data: dict[str, Any] | None = None, timeout: float = 120.0) -> Response:
 		"""专门用于文件上传的请求方法"""
-		headers = setting_manager.data.PROGRAM.HEADERS.copy()
+		if method.upper() in MUTATING_METHODS and BaseManager.is_safe_mode():
+			msg = f"安全模式下禁止上传文件: {endpoint}"
+			raise SafeModeError(msg)
+		headers = BaseHTTPClient.build_headers(setting_manager.data.PROGRAM.HEADERS)
 		if files:
 			headers.pop("Content-Type", None)
 			headers.pop("Content-Length", None)
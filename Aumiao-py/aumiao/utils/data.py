@@ -2,40 +2,148 @@ from __future__ import annotations
 
 from collections import UserDict
 from dataclasses import MISSING, asdict, dataclass, field, fields, is_dataclass, replace
-from json import JSONDecodeError, dump, dumps, load
+from fnmatch import fnmatch
+from functools import lru_cache
+from gzip import open as gzip_open
+from hashlib import sha256
+from json import JSONDecodeError, dump, dumps, load, loads
+from os import SEEK_END, environ
 from pathlib import Path
-from typing import TYPE_CHECKING, Any, Literal, TypeVar, cast, get_args, get_origin, get_type_hints
+from random import Random
+from re import compile as re_compile
+from re import error as re_error
+from shutil import copy2, copytree
+from sys import platform
+from threading import RLock
+from time import localtime, strftime, time
+from typing import TYPE_CHECKING, Any, ClassVar, Literal, Protocol, TypeVar, cast, get_args, get_origin, get_type_hints
 
 if TYPE_CHECKING:
-	from collections.abc import Mapping
-from aumiao.utils import decorator
+	from collections.abc import Callable, Iterable, Mapping, Sequence
+	from re import Pattern
+from aumiao.utils import decorator, tool
+
+try:
+	import keyring
+except ImportError:
+	# keyring 为可选依赖, 未安装时凭据只能保存在 data.json 中
+	keyring = None
 
 # 改进的类型定义
 T = TypeVar("T")
 DataclassInstance = Any
 
 
+# 迁移到平台数据目录时需要复制的旧目录
+LEGACY_DATA_DIRS = ("data", "cache")
+
+
+def default_data_root(system: str = platform, env: Mapping[str, str] = environ, home: Path | None = None, override: Path | None = None) -> Path:
+	"""
+	获取数据根目录, 优先级: AUMIAO_DATA_DIR 环境变量 > override (即 --data-dir) > 平台数据目录
+	Windows 为 %APPDATA%/Aumiao, macOS 为 ~/Library/Application Support/Aumiao, 其他系统为 $XDG_DATA_HOME/aumiao
+	"""
+	if env.get("AUMIAO_DATA_DIR"):
+		return Path(env["AUMIAO_DATA_DIR"]).expanduser()
+	if override is not None:
+		return override.expanduser()
+	home = home or Path.home()
+	if system == "win32":
+		return Path(env["APPDATA"]) / "Aumiao" if env.get("APPDATA") else home / "AppData" / "Roaming" / "Aumiao"
+	if system == "darwin":
+		return home / "Library" / "Application Support" / "Aumiao"
+	return (Path(env["XDG_DATA_HOME"]) if env.get("XDG_DATA_HOME") else home / ".local" / "share") / "aumiao"
+
+
+def has_legacy_data(source: Path, target: Path) -> bool:
+	"""工作目录下存在旧版 data/, 且数据根目录中的 data/ 为空 (尚未迁移或使用) 时返回 True"""
+	if source.resolve() == target.resolve() or not (source / "data").is_dir():
+		return False
+	return not (target / "data").is_dir() or not any((target / "data").iterdir())
+
+
+def migrate_legacy_data(source: Path, target: Path) -> list[Path]:
+	"""
+	将旧版保存在工作目录下的 data/ 与 cache/ 复制到新的数据根目录, 返回已复制的目录
+	目标的 data/ 中已有文件时视为已迁移, 不做任何操作; 原目录保留不删除
+	只在用户确认后调用, 不会自动执行
+	"""
+	if not has_legacy_data(source, target):
+		return []
+	migrated: list[Path] = []
+	for name in LEGACY_DATA_DIRS:
+		if not (source / name).is_dir():
+			continue
+		try:
+			copytree(source / name, target / name, dirs_exist_ok=True)
+		except OSError as e:
+			print(f"Warning: 迁移 {source / name} 失败: {e}")
+			continue
+		migrated.append(target / name)
+		print(f"已将 {source / name} 迁移到 {target / name}")
+	return migrated
+
+
+class LazyPath:
+	"""在每次访问时才解析的路径, base 为 data 时相对数据根目录, 为 work 时相对当前工作目录"""
+
+	def __init__(self, *parts: str, base: Literal["data", "work"] = "data") -> None:
+		self._base = base
+		self._parts = parts
+
+	def __get__(self, instance: object, owner: type[PathConfig]) -> Path:
+		root = owner.data_root() if self._base == "data" else Path.cwd()
+		return root.joinpath(*self._parts)
+
+
 # 路径处理改进
 class PathConfig:
-	"""集中管理所有路径配置"""
-
-	CURRENT_DIR = Path.cwd()
-	CACHE_DIR = CURRENT_DIR / "cache"
-	DATA_DIR = CURRENT_DIR / "data"
-	DOWNLOAD_DIR = CURRENT_DIR / "download"
-	JS_DIR: Path = CURRENT_DIR / "js_module"
+	"""
+	集中管理所有路径配置, 数据路径在首次访问时才确定数据根目录
+	默认使用平台数据目录, 避免在不同目录运行时产生多份数据; 下载内容、脚本模块与请求日志仍保存在工作目录
+	"""
+
+	_data_dir_override: ClassVar[Path | None] = None
+	_resolved_root: ClassVar[Path | None] = None
+	_root_lock: ClassVar[RLock] = RLock()
+	CURRENT_DIR = LazyPath()
+	BACKUP_DIR = LazyPath("data", "backups")
+	CACHE_DIR = LazyPath("cache")
+	DATA_DIR = LazyPath("data")
+	DOWNLOAD_DIR = LazyPath("download", base="work")
+	JS_DIR = LazyPath("js_module", base="work")
+	LOG_DIR = LazyPath("logs", base="work")
 	# PLUGIN_DIR = CURRENT_DIR / "plugins"
 	# 文件夹嵌套文件夹
-	COMPILE_FILE_PATH = DOWNLOAD_DIR / "compile"
-	FICTION_FILE_PATH = DOWNLOAD_DIR / "fiction"
+	COMPILE_FILE_PATH = LazyPath("download", "compile", base="work")
+	FICTION_FILE_PATH = LazyPath("download", "fiction", base="work")
 	# 数据文件路径
-	CACHE_FILE_PATH = CACHE_DIR / "info.json"
-	CAPTCHA_FILE_PATH: Path = CACHE_DIR / "captcha.jpg"
-	DATA_FILE_PATH = DATA_DIR / "data.json"
-	HISTORY_FILE_PATH = CACHE_DIR / "history.json"
-	SETTING_FILE_PATH = DATA_DIR / "setting.json"
-	TOKEN_FILE_PATH = DATA_DIR / "token.txt"
-	PASSWORD_FILE_PATH = DATA_DIR / "password.txt"
+	CACHE_FILE_PATH = LazyPath("cache", "info.json")
+	CACHE_HISTORY_FILE_PATH = LazyPath("cache", "info_history.jsonl")
+	CAPTCHA_FILE_PATH = LazyPath("cache", "captcha.jpg")
+	DATA_FILE_PATH = LazyPath("data", "data.json")
+	HISTORY_FILE_PATH = LazyPath("cache", "history.json")
+	SETTING_FILE_PATH = LazyPath("data", "setting.json")
+	TOKEN_FILE_PATH = LazyPath("data", "token.txt")
+	PASSWORD_FILE_PATH = LazyPath("data", "password.txt")
+
+	@classmethod
+	def set_data_dir(cls, path: Path | str) -> None:
+		"""指定数据根目录 (对应命令行的 --data-dir), 必须在首次访问数据路径之前调用; AUMIAO_DATA_DIR 环境变量仍然优先"""
+		with cls._root_lock:
+			if cls._resolved_root is not None:
+				msg = f"数据目录已确定为 {cls._resolved_root}, set_data_dir 必须在首次访问数据之前调用"
+				raise RuntimeError(msg)
+			cls._data_dir_override = Path(path)
+
+	@classmethod
+	def data_root(cls) -> Path:
+		"""获取数据根目录, 首次调用时确定并创建所需目录, 之后不再改变"""
+		with cls._root_lock:
+			if cls._resolved_root is None:
+				cls._resolved_root = default_data_root(override=cls._data_dir_override)
+				cls.ensure_directories()
+			return cls._resolved_root
 
 	@classmethod
 	def ensure_directories(cls) -> None:
@@ -55,10 +163,40 @@ class PathConfig:
 		]
 
 
-# 初始化路径配置
-PathConfig.ensure_directories()
 # 类型别名
 ReadType = Literal["COMMENT_REPLY", "LIKE_FORK", "SYSTEM"]
+# 请求日志级别: none 不记录, errors 只记录失败响应, all 记录全部
+LogLevel = Literal["none", "errors", "all"]
+# 账号密码的保存位置: file 保存在 data.json, keyring 保存在系统钥匙串
+CredentialStore = Literal["file", "keyring"]
+# 黑名单条目的来源: 手动添加、刷屏自动拉黑、举报自动拉黑
+BlackRoomSource = Literal["manual", "auto_spam", "auto_report"]
+# 自动回复的匹配方式: 包含关键词、与问题完全一致、正则表达式搜索
+AnswerMatchMode = Literal["contains", "exact", "regex"]
+# 使用统计的汇总范围: 当天、当月、当年、全部
+UsagePeriod = Literal["day", "month", "year", "all"]
+# 配置校验规则
+HEADER_NAME_PATTERN = re_compile(r"^[!#$%&'*+\-.^_`|~0-9A-Za-z]+$")
+HEADER_VALUE_PATTERN = re_compile(r"^[\t\x20-\x7e]*$")
+PASSWORD_LOGIN_METHODS = ("", "password_v0", "password_v1", "password_v2", "token")
+# 每个数据文件保留的备份数量
+BACKUP_KEEP_COUNT = 5
+# 系统钥匙串中的服务名, 密码保存在钥匙串时 data.json 中只保留该标记
+KEYRING_SERVICE = "aumiao"
+KEYRING_MARKER = "<keyring>"
+# setting.json 中保留的恢复记录数量
+INCIDENT_KEEP_COUNT = 20
+# 配置档案: setting.<name>.json 只包含覆盖项, default 档案直接使用 setting.json
+PROFILE_ENV_VAR = "AUMIAO_PROFILE"
+DEFAULT_PROFILE = "default"
+PROFILE_NAME_PATTERN = re_compile(r"^[A-Za-z0-9_-]+$")
+# 使用统计先在内存中累积, 停止计数该秒数后写入 cache.json
+USAGE_FLUSH_SECONDS = 5.0
+# USER_DATA 中需要规范化的列表字段, 其中部分字段与顺序无关, 排序后便于比较差异
+USER_DATA_LIST_FIELDS = ("ads", "black_room", "comments", "emojis", "replies")
+USER_DATA_SORTED_FIELDS = ("ads", "black_room")
+# 保存为 CommentTemplate 的字段, 按 text 规范化
+USER_DATA_TEMPLATE_FIELDS = ("comments", "replies")
 
 
 # --------------------------
@@ -75,14 +213,118 @@ class AccountData:
 	password: str = ""
 
 
+@dataclass
+class BlackRoomEntry:
+	added_at: int = 0
+	nickname: str = ""
+	reason: str = ""
+	source: BlackRoomSource = "manual"
+	user_id: str = ""
+
+	@classmethod
+	def coerce(cls, value: object) -> BlackRoomEntry:
+		"""兼容旧版本的纯字符串格式, 迁移为只有 user_id 的条目"""
+		if isinstance(value, dict):
+			return DataClassConverter.dict_to_dataclass(cls, value)
+		return cls(user_id=str(value))
+
+
+@lru_cache(maxsize=256)
+def compile_answer_pattern(pattern: str) -> Pattern[str] | None:
+	"""编译并缓存自动回复的正则表达式, 无效时打印警告 (每个表达式只警告一次) 并返回 None"""
+	try:
+		return re_compile(pattern)
+	except re_error as e:
+		print(f"Warning: 自动回复的正则表达式 {pattern!r} 无效, 该条目已停用: {e}")
+		return None
+
+
+@dataclass
+class Answer:
+	enabled: bool = True
+	match_mode: AnswerMatchMode = "contains"
+	question: str = ""
+	# 无法识别的旧格式条目原样保存在这里, 不参与匹配
+	raw: dict[str, Any] = field(default_factory=dict)
+	responses: list[str] = field(default_factory=list)
+
+	@classmethod
+	def coerce(cls, value: object) -> Answer | list[Answer]:
+		"""
+		兼容旧版本的 {关键词: 回复或回复列表} 格式, 一个字典包含多个关键词时展开为多个条目
+		无法识别的格式保存到 raw 并停用; 正则表达式无效时同样停用, 不影响读取其他数据
+		"""
+		if isinstance(value, dict) and "question" in value:
+			answer = DataClassConverter.dict_to_dataclass(cls, {key: item for key, item in value.items() if key != "raw"})
+			raw = value.get("raw")
+			answer = replace(answer, raw=dict(raw)) if isinstance(raw, dict) else answer
+		elif isinstance(value, dict) and value and all(cls._is_legacy_response(item) for item in value.values()):
+			answers = [cls(question=str(key), responses=[item] if isinstance(item, str) else list(item)) for key, item in value.items()]
+			return answers[0] if len(answers) == 1 else answers
+		else:
+			print(f"Warning: 无法识别的自动回复条目, 已停用并原样保留: {value!r}")
+			return cls(enabled=False, raw=value if isinstance(value, dict) else {"value": value})
+		if answer.match_mode == "regex" and compile_answer_pattern(answer.question) is None:
+			return replace(answer, enabled=False)
+		return answer
+
+	@staticmethod
+	def _is_legacy_response(value: object) -> bool:
+		return isinstance(value, str) or (isinstance(value, list) and all(isinstance(item, str) for item in value))
+
+	def matches(self, text: str) -> bool:
+		"""判断文本是否命中该条目, 停用的条目和无效的正则表达式不会命中"""
+		if not self.enabled or not self.question:
+			return False
+		if self.match_mode == "exact":
+			return text.strip() == self.question.strip()
+		if self.match_mode == "regex":
+			pattern = compile_answer_pattern(self.question)
+			return pattern is not None and pattern.search(text) is not None
+		return self.question in text
+
+
+def find_answer(answers: Iterable[Answer], text: str) -> Answer | None:
+	"""按顺序返回第一个命中文本的自动回复条目"""
+	return next((answer for answer in answers if answer.matches(text)), None)
+
+
+@dataclass
+class CommentTemplate:
+	created_at: int | None = None
+	tags: list[str] = field(default_factory=list)
+	text: str = ""
+	# 随机选择时的权重, 不大于 0 时不会被选中
+	weight: float = 1.0
+
+	@classmethod
+	def coerce(cls, value: object) -> CommentTemplate:
+		"""兼容旧版本的纯字符串格式, 迁移为只有 text 的模板"""
+		if isinstance(value, dict):
+			return DataClassConverter.dict_to_dataclass(cls, value)
+		return cls(text=str(value))
+
+
 @dataclass
 class UserData:
 	ads: list[str] = field(default_factory=list)
-	answers: list[dict[str, str | list[str]]] = field(default_factory=list)
-	black_room: list[str] = field(default_factory=list)
-	comments: list[str] = field(default_factory=list)
+	answers: list[Answer] = field(default_factory=list)
+	black_room: list[BlackRoomEntry] = field(default_factory=list)
+	comments: list[CommentTemplate] = field(default_factory=list)
 	emojis: list[str] = field(default_factory=list)
-	replies: list[str] = field(default_factory=list)
+	replies: list[CommentTemplate] = field(default_factory=list)
+
+	def find_answer(self, text: str) -> Answer | None:
+		"""查找与评论内容匹配的自动回复, 没有命中时返回 None"""
+		return find_answer(self.answers, text)
+
+
+def pick_template(templates: Sequence[CommentTemplate], rng: Random | None = None) -> CommentTemplate | None:
+	"""按权重随机选择模板, 所有权重都不大于 0 时返回 None"""
+	candidates = [template for template in templates if template.weight > 0]
+	if not candidates:
+		return None
+	return (rng or Random()).choices(candidates, weights=[template.weight for template in candidates])[0]
 
 
 @dataclass
@@ -95,10 +337,25 @@ class CodeMaoData:
 @dataclass
 class Parameter:
 	all_read_type: list[ReadType] = field(default_factory=list)
+	cache_ttl_minutes: int = 60
+	check_connectivity_on_start: bool = False
+	# 账号密码的保存位置, 可选 file 或 keyring (需安装 keyring)
+	credential_store: str = "file"
+	dry_run: bool = False
+	# 按 URL 前缀覆盖日志级别, 匹配多个前缀时取最长者
+	endpoint_log_overrides: dict[str, str] = field(default_factory=dict)
 	log: bool = False
+	# 全局日志级别, 为空时由 log 决定 (true 为 all, false 为 none)
+	log_level: str = ""
+	# 读取数据文件时允许的最大字节数, 0 表示不限制
+	max_data_file_bytes: int = 0
+	normalize_user_data: bool = False
 	password_login_method: str = ""
 	report_work_max: int = 0
+	safe_mode: bool = False
 	spam_del_max: int = 0
+	# 在 cache.json 中记录各功能的使用次数, 只保存在本地
+	usage_stats: bool = False
 
 
 @dataclass
@@ -112,10 +369,20 @@ class More:
 	stream: bool = False
 
 
+@dataclass
+class RecoveryRecord:
+	corrupt_path: str = ""
+	file: str = ""
+	restored_from: str = ""
+	timestamp: int = 0
+
+
 @dataclass
 class Program:
 	AUTHOR: str = ""
 	HEADERS: dict[str, str] = field(default_factory=dict)
+	# 最近的损坏文件恢复记录, 由程序自动写入
+	INCIDENTS: list[RecoveryRecord] = field(default_factory=list)
 	MEMBER: str = ""
 	SLOGAN: str = ""
 	TEAM: str = ""
@@ -131,6 +398,21 @@ class UploadHistory:
 	upload_time: int = 0
 
 
+@dataclass
+class RecoveryIncident:
+	corrupt_path: Path | None = None
+	file: str = ""
+	restored_from: Path | None = None
+	timestamp: int = 0
+
+
+@dataclass
+class FileEntry:
+	modified: float = 0.0
+	path: Path = field(default_factory=Path)
+	size: int = 0
+
+
 @dataclass
 class CodeMaoCache:
 	collected: int = 0
@@ -139,9 +421,18 @@ class CodeMaoCache:
 	liked: int = 0
 	nickname: str = ""
 	timestamp: int = 0
+	# 按日期 (YYYY-MM-DD) 分组的功能使用次数, 仅在开启 usage_stats 时记录
+	usage: dict[str, dict[str, int]] = field(default_factory=dict)
 	user_id: int = 0
 	view: int = 0
 
+	def is_stale(self, max_age: float, now: float | None = None) -> bool:
+		"""判断缓存是否过期 (秒), 未记录时间或时间戳位于未来 (时钟偏差) 均视为过期"""
+		current = time() if now is None else now
+		if self.timestamp <= 0 or self.timestamp > current:
+			return True
+		return current - self.timestamp > max_age
+
 
 @dataclass
 class CodeMaoSetting:
@@ -155,6 +446,12 @@ class CodemaoHistory:
 	history: list[UploadHistory] = field(default_factory=list)
 
 
+@dataclass
+class SettingError:
+	message: str = ""
+	path: str = ""
+
+
 # --------------------------
 # 默认配置数据
 # --------------------------
@@ -162,10 +459,20 @@ class CodemaoHistory:
 DEFAULT_SETTING_DATA = {
 	"PARAMETER": {
 		"all_read_type": ["COMMENT_REPLY", "LIKE_FORK", "SYSTEM"],
+		"cache_ttl_minutes": 60,
+		"check_connectivity_on_start": False,
+		"credential_store": "file",
+		"dry_run": False,
+		"endpoint_log_overrides": {},
 		"log": False,
+		"log_level": "",
+		"max_data_file_bytes": 0,
+		"normalize_user_data": False,
 		"password_login_method": "token",
 		"report_work_max": 8,
+		"safe_mode": False,
 		"spam_del_max": 3,
+		"usage_stats": False,
 	},
 	# "PLUGIN": {},
 	"PROGRAM": {
@@ -225,13 +532,13 @@ DEFAULT_DATA_DATA = {
 			"转发",
 		],
 		"answers": [
-			{"牢大": "孩子们, 我回来了"},
-			{"奶龙": "我才是奶龙"},
-			{"name": "I'm {nickname}"},
-			{"QQ": "It's {qq_number}"},
-			{"只因": ["不许你黑我家鸽鸽!😡", "想要绿尸函了食不食?", "香精煎鱼食不食?"]},
+			{"question": "牢大", "responses": ["孩子们, 我回来了"]},
+			{"question": "奶龙", "responses": ["我才是奶龙"]},
+			{"question": "name", "responses": ["I'm {nickname}"]},
+			{"question": "QQ", "responses": ["It's {qq_number}"]},
+			{"question": "只因", "responses": ["不许你黑我家鸽鸽!😡", "想要绿尸函了食不食?", "香精煎鱼食不食?"]},
 		],
-		"black_room": ["2233", "114514", "1919810"],
+		"black_room": [{"user_id": "2233"}, {"user_id": "114514"}, {"user_id": "1919810"}],
 		"comments": ["666", "不错不错", "前排:P", "加油!:O", "沙发 */ω\\*", "针不戳:D"],
 		"emojis": [
 			"星能猫_好吃",
@@ -323,6 +630,14 @@ class DataClassConverter:
 		if not isinstance(value, list):
 			return []
 		if isinstance(item_type, type) and is_dataclass(item_type):
+			# 数据类可定义 coerce 类方法, 用于兼容旧版本保存的其他格式, 返回列表时展开为多个元素
+			coerce = getattr(item_type, "coerce", None)
+			if coerce is not None:
+				result: list[Any] = []
+				for item in value:
+					coerced = coerce(item)
+					result.extend(coerced if isinstance(coerced, list) else [coerced])
+				return result
 			return [cls.dict_to_dataclass(item_type, item) for item in value]  # ty:ignore[invalid-argument-type]
 		if get_origin(item_type) is Literal:
 			# 特殊处理列表中的 Literal 类型
@@ -359,48 +674,273 @@ class DataClassConverter:
 			return value
 
 
+# --------------------------
+# 配置校验
+# --------------------------
+class SettingValidator:
+	"""setting.json 字段级校验工具"""
+
+	@classmethod
+	def validate(cls, setting: CodeMaoSetting) -> list[SettingError]:
+		"""校验配置实例, 一次性返回所有错误"""
+		errors: list[SettingError] = []
+		parameter = setting.PARAMETER
+		valid_read_types = get_args(ReadType)
+		errors.extend(
+			SettingError(message=f"未知的消息类型 {read_type!r}, 可选值: {valid_read_types}", path=f"PARAMETER.all_read_type[{index}]")
+			for index, read_type in enumerate(parameter.all_read_type)
+			if read_type not in valid_read_types
+		)
+		for name in ("report_work_max", "spam_del_max"):
+			value = getattr(parameter, name)
+			if not isinstance(value, int) or value < 0:
+				errors.append(SettingError(message=f"取值应为非负整数, 实际为 {value!r}", path=f"PARAMETER.{name}"))
+		valid_stores = get_args(CredentialStore)
+		if parameter.credential_store not in valid_stores:
+			errors.append(SettingError(message=f"未知的凭据存储 {parameter.credential_store!r}, 可选值: {valid_stores}", path="PARAMETER.credential_store"))
+		valid_log_levels = get_args(LogLevel)
+		if parameter.log_level and parameter.log_level not in valid_log_levels:
+			errors.append(SettingError(message=f"未知的日志级别 {parameter.log_level!r}, 可选值: {valid_log_levels}", path="PARAMETER.log_level"))
+		errors.extend(
+			SettingError(message=f"未知的日志级别 {level!r}, 可选值: {valid_log_levels}", path=f"PARAMETER.endpoint_log_overrides.{prefix}")
+			for prefix, level in parameter.endpoint_log_overrides.items()
+			if level not in valid_log_levels
+		)
+		if not isinstance(parameter.max_data_file_bytes, int) or parameter.max_data_file_bytes < 0:
+			errors.append(SettingError(message=f"取值应为非负整数, 实际为 {parameter.max_data_file_bytes!r}", path="PARAMETER.max_data_file_bytes"))
+		if not isinstance(parameter.cache_ttl_minutes, int) or parameter.cache_ttl_minutes < 0:
+			errors.append(SettingError(message=f"取值应为非负整数, 实际为 {parameter.cache_ttl_minutes!r}", path="PARAMETER.cache_ttl_minutes"))
+		if parameter.password_login_method not in PASSWORD_LOGIN_METHODS:
+			errors.append(
+				SettingError(message=f"未知的登录方式 {parameter.password_login_method!r}, 可选值: {PASSWORD_LOGIN_METHODS[1:]}", path="PARAMETER.password_login_method"),
+			)
+		for header_name, header_value in setting.PROGRAM.HEADERS.items():
+			path = f"PROGRAM.HEADERS.{header_name}"
+			if not HEADER_NAME_PATTERN.match(header_name):
+				errors.append(SettingError(message=f"请求头名称 {header_name!r} 不是合法的 HTTP token", path=path))
+			if not isinstance(header_value, str):
+				errors.append(SettingError(message=f"请求头的值应为字符串, 实际为 {header_value!r}", path=path))
+				continue
+			# 与客户端构建请求头时一致: 先替换 ${VAR} 再检查替换后的值
+			try:
+				header_value = tool.StringProcessor().interpolate_env_vars(header_value)  # noqa: PLW2901
+			except ValueError as e:
+				errors.append(SettingError(message=str(e), path=path))
+				continue
+			if not HEADER_VALUE_PATTERN.match(header_value):
+				errors.append(SettingError(message="请求头的值包含换行、控制字符或非 ASCII 字符", path=path))
+		return errors
+
+	@classmethod
+	def find_unknown_keys(cls, data_class: type, raw: Mapping[str, Any], prefix: str = "") -> list[str]:
+		"""查找数据类中不存在的键, 通常是拼写错误"""
+		field_types = get_type_hints(data_class)
+		unknown: list[str] = []
+		for key, value in raw.items():
+			path = f"{prefix}{key}"
+			if key not in field_types:
+				unknown.append(path)
+				continue
+			field_type = field_types[key]
+			if isinstance(field_type, type) and is_dataclass(field_type) and isinstance(value, dict):
+				unknown.extend(cls.find_unknown_keys(field_type, value, f"{path}."))
+		return unknown
+
+
 # --------------------------
 # 增强型文件操作
 # --------------------------
+class ProfileError(ValueError):
+	"""配置档案名称无效、文件不存在或合并后的配置无法通过校验"""
+
+
+class FileTooLargeError(ValueError):
+	"""文件超过 max_data_file_bytes 限制, 与内容损坏区分, 不会触发备份恢复"""
+
+
 class JsonFileHandler:
 	"""JSON 文件处理器"""
 
+	_incidents: ClassVar[list[RecoveryIncident]] = []
+
 	@staticmethod
 	def load_json_file(path: Path, data_class: type[T], *, create_if_missing: bool = True) -> T:
 		"""从 JSON 文件加载数据到数据类, 如果文件不存在则创建"""
 		try:
 			if not path.exists():
 				if create_if_missing:
-					print(f"文件 {path.name} 不存在, 使用默认值创建...")
+					print(f"文件 {path.name} 不存在, 使用默认值创建于 {path}")
 					# 根据路径选择默认数据
 					default_data = {}
 					if path == PathConfig.SETTING_FILE_PATH:
 						default_data = DEFAULT_SETTING_DATA
 					elif path == PathConfig.DATA_FILE_PATH:
 						default_data = DEFAULT_DATA_DATA
-					# 创建默认实例并保存
+					# 创建默认实例并保存, 安全模式下只在内存中使用
 					instance = DataClassConverter.dict_to_dataclass(data_class, default_data)
-					JsonFileHandler.save_json_file(path, instance)
+					if JsonFileHandler._writes_blocked(path):
+						print(f"[safe_mode] 跳过创建 {path.name}")
+					else:
+						JsonFileHandler.save_json_file(path, instance)
 					return instance
 				return data_class()
-			with path.open(encoding="utf-8") as f:
-				data = load(f)
-			# 预处理 Literal 类型字段
-			field_types = get_type_hints(data_class)
-			for field_name, field_type in field_types.items():
-				if field_name in data and get_origin(field_type) is Literal:
-					valid_values = get_args(field_type)
-					if data[field_name] not in valid_values:
-						data[field_name] = valid_values[0] if valid_values else None
-			return DataClassConverter.dict_to_dataclass(data_class, data)
+			JsonFileHandler._check_size(path)
+			return JsonFileHandler._read_json_file(path, data_class)
+		except FileTooLargeError:
+			raise
 		except (JSONDecodeError, ValueError) as e:
 			print(f"Error loading {path.name}: {e}")
-			print("使用默认值...")
-			return data_class()
+			return JsonFileHandler._recover(path, data_class)
 		except Exception as e:
 			print(f"Unexpected error loading {path.name}: {e}")
 			return data_class()
 
+	@staticmethod
+	def _check_size(path: Path) -> None:
+		"""读取前检查文件大小; setting.json 本身提供该限制, 不做检查"""
+		if path == PathConfig.SETTING_FILE_PATH:
+			return
+		limit = SettingManager().data.PARAMETER.max_data_file_bytes
+		size = path.stat().st_size
+		if limit > 0 and size > limit:
+			msg = f"文件过大: {path.name} 为 {size} 字节, 超过 max_data_file_bytes ({limit})"
+			raise FileTooLargeError(msg)
+
+	@staticmethod
+	def _writes_blocked(path: Path) -> bool:
+		"""安全模式下禁止写入; setting.json 尚未创建时无法读取配置, 只看运行时设置"""
+		if path == PathConfig.SETTING_FILE_PATH:
+			return bool(BaseManager._safe_mode)  # noqa: SLF001
+		return BaseManager.is_safe_mode()
+
+	@staticmethod
+	def _read_json_file(path: Path, data_class: type[T]) -> T:
+		"""读取 JSON 文件并转换为数据类, 解析失败时抛出异常"""
+		# utf-8-sig 兼容 Windows 编辑器写入的 BOM
+		with path.open(encoding="utf-8-sig") as f:
+			data = load(f)
+		# 预处理 Literal 类型字段
+		field_types = get_type_hints(data_class)
+		for field_name, field_type in field_types.items():
+			if field_name in data and get_origin(field_type) is Literal:
+				valid_values = get_args(field_type)
+				if data[field_name] not in valid_values:
+					data[field_name] = valid_values[0] if valid_values else None
+		return DataClassConverter.dict_to_dataclass(data_class, data)
+
+	@staticmethod
+	def _recover(path: Path, data_class: type[T]) -> T:
+		"""
+		损坏文件的恢复流程: 移走损坏文件, 依次尝试最新的备份, 全部失败时使用默认值
+		安全模式下不移动、不覆盖任何文件, 备份内容只在内存中使用
+		"""
+		blocked = JsonFileHandler._writes_blocked(path)
+		if blocked:
+			print(f"[safe_mode] 跳过移走损坏的文件 {path.name}")
+		incident = RecoveryIncident(corrupt_path=None if blocked else JsonFileHandler.preserve_corrupt_file(path), file=path.name, timestamp=int(time()))
+		JsonFileHandler._incidents.append(incident)
+		instance = None
+		for backup in JsonFileHandler.list_backups(path):
+			try:
+				instance = JsonFileHandler._read_json_file(backup, data_class)
+			except (OSError, JSONDecodeError, ValueError, TypeError) as e:
+				print(f"备份 {backup.name} 无法解析, 跳过: {e}")
+				continue
+			incident.restored_from = backup
+			if blocked:
+				print(f"[safe_mode] 使用备份 {backup.name} 的内容, 未写回 {path.name}")
+			else:
+				copy2(backup, path)
+				print(f"已从备份 {backup.name} 恢复 {path.name}")
+			break
+		if instance is None:
+			print("没有可用的备份, 使用默认值...")
+			instance = data_class()
+		JsonFileHandler._record_incident(incident, instance)
+		return instance
+
+	@staticmethod
+	def _record_incident(incident: RecoveryIncident, instance: object) -> None:
+		"""将恢复记录写入 setting.json 的 PROGRAM.INCIDENTS, 只保留最近的若干条"""
+		record = RecoveryRecord(
+			corrupt_path=str(incident.corrupt_path or ""),
+			file=incident.file,
+			restored_from=str(incident.restored_from or ""),
+			timestamp=incident.timestamp,
+		)
+
+		def append(setting: CodeMaoSetting) -> None:
+			setting.PROGRAM.INCIDENTS.append(record)
+			del setting.PROGRAM.INCIDENTS[:-INCIDENT_KEEP_COUNT]
+
+		# setting.json 自身恢复时 SettingManager 尚未构造完成, 记录在恢复出的实例中, 随下次保存写入
+		if isinstance(instance, CodeMaoSetting):
+			append(instance)
+		else:
+			SettingManager().update_with(append)
+
+	@staticmethod
+	def recover_report() -> list[RecoveryIncident]:
+		"""返回本次运行中发生的文件恢复记录"""
+		return list(JsonFileHandler._incidents)
+
+	@staticmethod
+	def preserve_corrupt_file(path: Path) -> Path | None:
+		"""将无法解析的文件重命名为 <name>.corrupt-<毫秒时间戳>, 避免后续保存时覆盖用户数据"""
+		corrupt_path = path.with_name(f"{path.name}.corrupt-{int(time() * 1000)}")
+		try:
+			path.replace(corrupt_path)
+		except OSError as e:
+			print(f"Warning: 无法保留损坏的文件 {path.name}: {e}")
+			return None
+		print(f"文件 {path.name} 已损坏, 原始内容已保存到 {corrupt_path}")
+		return corrupt_path
+
+	@staticmethod
+	def _backup_identity(path: Path) -> tuple[str, bool]:
+		"""返回用于区分备份的路径标识及文件是否位于数据目录内; 数据目录内使用相对路径, 迁移数据目录后备份仍可用"""
+		absolute = path.resolve()
+		root = PathConfig.data_root().resolve()
+		if absolute.is_relative_to(root):
+			return absolute.relative_to(root).as_posix(), True
+		return absolute.as_posix(), False
+
+	@staticmethod
+	def _backup_prefix(path: Path) -> str:
+		"""备份文件名前缀: 文件名加路径摘要, 避免不同目录下的同名文件共用备份"""
+		identity, _ = JsonFileHandler._backup_identity(path)
+		return f"{path.name}.{sha256(identity.encode()).hexdigest()[:8]}"
+
+	@staticmethod
+	def list_backups(path: Path) -> list[Path]:
+		"""列出文件的备份, 最新的在前"""
+		if not PathConfig.BACKUP_DIR.exists():
+			return []
+		prefix = JsonFileHandler._backup_prefix(path)
+		backups = [backup for backup in PathConfig.BACKUP_DIR.glob(f"{prefix}.*") if backup.suffix[1:].isdigit()]
+		# 旧版本只按文件名命名备份, 数据目录内的文件仍可使用这些备份
+		if JsonFileHandler._backup_identity(path)[1]:
+			backups += [backup for backup in PathConfig.BACKUP_DIR.glob(f"{path.name}.*") if backup.stem == path.name and backup.suffix[1:].isdigit()]
+		return sorted(backups, key=lambda backup: int(backup.suffix[1:]), reverse=True)
+
+	@staticmethod
+	def backup_file(path: Path) -> None:
+		"""覆盖前备份当前文件, 每个文件只保留最近的若干份"""
+		if not path.exists():
+			return
+		PathConfig.BACKUP_DIR.mkdir(parents=True, exist_ok=True)
+		# 同一毫秒内多次保存时顺延时间戳, 避免覆盖上一份备份
+		prefix = JsonFileHandler._backup_prefix(path)
+		stamp = int(time() * 1000)
+		while (PathConfig.BACKUP_DIR / f"{prefix}.{stamp}").exists():
+			stamp += 1
+		try:
+			copy2(path, PathConfig.BACKUP_DIR / f"{prefix}.{stamp}")
+			for stale in JsonFileHandler.list_backups(path)[BACKUP_KEEP_COUNT:]:
+				stale.unlink()
+		except OSError as e:
+			print(f"Warning: 无法备份文件 {path.name}: {e}")
+
 	@staticmethod
 	def save_json_file(path: Path, data: object) -> None:
 		"""将数据类实例保存到 JSON 文件"""
@@ -409,9 +949,11 @@ class JsonFileHandler:
 			raise ValueError(msg)
 		temp_file = path.with_suffix(".tmp")
 		try:
+			path.parent.mkdir(parents=True, exist_ok=True)
 			serialized = asdict(data)
 			with temp_file.open("w", encoding="utf-8") as f:
 				dump(serialized, f, ensure_ascii=False, indent=4, separators=(",", ":"))
+			JsonFileHandler.backup_file(path)
 			temp_file.replace(path)
 			print(f"文件 {path.name} 已保存")
 		except Exception as e:
@@ -442,15 +984,19 @@ class BaseManager[T]:
 	"""基础数据管理器"""
 
 	_data: T | None = None
-	_file_path: Path
 	_data_class: type[T]
+	# 运行时设置的安全模式, None 表示跟随配置中的 safe_mode
+	_safe_mode: bool | None = None
 
-	def __init__(self, file_path: Path, data_class: type[T]) -> None:
-		self._file_path = file_path
+	def __init__(self, file_path: Path | Callable[[], Path], data_class: type[T]) -> None:
+		# 传入函数时在首次使用时才解析路径, 文件不存在时在首次读取时创建, 以便启动时先应用数据目录设置
+		self._path_source = file_path
 		self._data_class = data_class
-		# 确保文件存在
-		if not file_path.exists():
-			JsonFileHandler.load_json_file(file_path, data_class, create_if_missing=True)
+		self._lock = RLock()
+
+	@property
+	def _file_path(self) -> Path:
+		return self._path_source() if callable(self._path_source) else self._path_source
 
 	@property
 	def data(self) -> T:
@@ -461,6 +1007,26 @@ class BaseManager[T]:
 
 	def update(self, new_data: dict[str, Any]) -> None:
 		"""更新数据"""
+		with self._lock:
+			self._apply_update(new_data)
+			self.save()
+
+	def update_with[R](self, func: Callable[[T], R]) -> R:
+		"""
+		在锁内就地修改数据并保存, 返回回调的返回值
+		回调抛出异常时不保存, 异常原样抛出
+		"""
+		with self._lock:
+			result = func(self.data)
+			self.save()
+			return result
+
+	def read_with[R](self, func: Callable[[T], R]) -> R:
+		"""在锁内读取数据, 避免为读取单个字段复制整个数据"""
+		with self._lock:
+			return func(self.data)
+
+	def _apply_update(self, new_data: dict[str, Any]) -> None:
 		for key, value in new_data.items():
 			if not hasattr(self.data, key):
 				continue
@@ -473,27 +1039,61 @@ class BaseManager[T]:
 				# 创建有效字段的字典
 				valid_fields = {f.name for f in fields(current)}
 				filtered_value = {k: v for k, v in value.items() if k in valid_fields}
-				# 使用 replace 更新实例
-				updated_value = replace(current, **filtered_value)
+				# 与读取文件时一样转换类型 (如嵌套数据类列表), 再使用 replace 更新实例
+				converted = DataClassConverter.dict_to_dataclass(type(current), filtered_value)
+				updated_value = replace(current, **{k: getattr(converted, k) for k in filtered_value})
 				setattr(self.data, key, updated_value)
 			else:
 				setattr(self.data, key, value)
-		self.save()
 
 	def reset(self, *fields_to_reset: str) -> None:
 		"""重置指定字段到默认值"""
-		data_instance = cast("DataclassInstance", self.data)
-		for f in fields(data_instance):
-			if f.name in fields_to_reset:
-				if f.default is not MISSING:
-					setattr(self.data, f.name, f.default)
-				elif f.default_factory is not MISSING:
-					setattr(self.data, f.name, f.default_factory())
-		self.save()
+		with self._lock:
+			data_instance = cast("DataclassInstance", self.data)
+			for f in fields(data_instance):
+				if f.name in fields_to_reset:
+					if f.default is not MISSING:
+						setattr(self.data, f.name, f.default)
+					elif f.default_factory is not MISSING:
+						setattr(self.data, f.name, f.default_factory())
+			self.save()
 
 	def save(self) -> None:
-		"""保存数据到文件"""
-		JsonFileHandler.save_json_file(self._file_path, self.data)
+		"""保存数据到文件, 安全模式下只记录不写入"""
+		if BaseManager.is_safe_mode():
+			# 只输出变更的字段名, 避免账号密码等敏感内容出现在终端日志中
+			changed = self._changed_fields()
+			print(f"[safe_mode] 跳过写入 {self._file_path.name}, 变更字段: {', '.join(changed) or '无'}")
+			return
+		JsonFileHandler.save_json_file(self._file_path, self._data_for_file())
+
+	def _data_for_file(self) -> T:
+		"""写入文件的数据, 子类可覆盖以替换不应落盘的字段"""
+		return self.data
+
+	def _changed_fields(self) -> list[str]:
+		"""对比文件内容, 返回内存中发生变化的顶层字段名"""
+		current = asdict(cast("DataclassInstance", self._data_for_file()))
+		try:
+			with self._file_path.open(encoding="utf-8-sig") as f:
+				saved = load(f)
+		except (OSError, JSONDecodeError):
+			saved = {}
+		if not isinstance(saved, dict):
+			saved = {}
+		return [key for key, value in current.items() if saved.get(key) != value]
+
+	@staticmethod
+	def set_safe_mode(*, enabled: bool | None) -> None:
+		"""运行时开启或关闭安全模式, 传入 None 恢复为跟随配置"""
+		BaseManager._safe_mode = enabled
+
+	@staticmethod
+	def is_safe_mode() -> bool:
+		"""安全模式下数据只在内存中修改, 不写入文件, 且客户端拒绝发送修改类请求"""
+		if BaseManager._safe_mode is not None:
+			return BaseManager._safe_mode
+		return SettingManager().data.PARAMETER.safe_mode
 
 	def reload(self) -> None:
 		"""重新加载数据"""
@@ -504,6 +1104,53 @@ class BaseManager[T]:
 		return self._data_class
 
 
+# --------------------------
+# 系统钥匙串
+# --------------------------
+class KeyringBackend(Protocol):
+	def get_password(self, service_name: str, username: str) -> str | None: ...
+
+	def set_password(self, service_name: str, username: str, password: str) -> None: ...
+
+	def delete_password(self, service_name: str, username: str) -> None: ...
+
+
+class CredentialVault:
+	"""系统钥匙串访问入口, 未安装 keyring 或没有可用后端 (如无图形环境的 Linux) 时视为不可用"""
+
+	_backend: ClassVar[KeyringBackend | None] = None
+	_warned: ClassVar[bool] = False
+
+	@classmethod
+	def set_backend(cls, backend: KeyringBackend | None) -> None:
+		"""替换钥匙串后端, 传入 None 恢复为 keyring 的默认后端, 主要用于测试"""
+		cls._backend = backend
+
+	@classmethod
+	def backend(cls) -> KeyringBackend | None:
+		if cls._backend is not None:
+			return cls._backend
+		if keyring is None:
+			return None
+		try:
+			backend = keyring.get_keyring()
+		except Exception:
+			return None
+		# keyring 找不到可用后端时返回优先级为 0 的 fail 后端
+		return backend if getattr(backend, "priority", 0) > 0 else None
+
+	@classmethod
+	def available(cls) -> bool:
+		return cls.backend() is not None
+
+	@classmethod
+	def warn_unavailable(cls) -> None:
+		"""提示回退到文件存储, 每次运行只提示一次"""
+		if not cls._warned:
+			cls._warned = True
+			print("Warning: 没有可用的系统钥匙串 (需安装 keyring 并配置后端), 密码仍保存在 data.json 中")
+
+
 # --------------------------
 # 单例管理器
 # --------------------------
@@ -532,8 +1179,15 @@ class CodeMaoFile:
 			# 字节内容强制使用二进制模式
 			if "b" not in mode:
 				mode += "b"
+		# .gz 文件透明压缩, 文本内容需使用 gzip 的文本模式
+		if path.suffix == ".gz":
+			if "b" not in mode:
+				mode += "t"
+			opener = gzip_open
+		else:
+			opener = Path.open
 		# 打开文件并写入
-		with Path.open(path, mode, **kwargs) as f:
+		with decorator.TimingRegistry().measure(f"file_write {path.name}"), opener(path, mode, **kwargs) as f:
 			if isinstance(content, (str, bytes)):
 				f.write(content)
 			elif isinstance(content, dict):
@@ -542,29 +1196,670 @@ class CodeMaoFile:
 			elif isinstance(content, list):
 				f.writelines(line + "\n" for line in content)
 
+	@staticmethod
+	def file_size(path: Path) -> int:
+		"""获取文件大小 (字节), 可在读取前预先检查"""
+		return path.stat().st_size
 
-@decorator.singleton
+	@staticmethod
+	def file_load_gz(path: Path, encoding: str = "utf-8", max_bytes: int | None = None) -> Any:
+		"""
+		读取并解压 .json.gz 文件, 返回解析后的 JSON 数据
+		解压后内容超过 max_bytes 时抛出 FileTooLargeError, 缺省取配置中的 max_data_file_bytes (0 表示不限制)
+		"""
+		limit = SettingManager().data.PARAMETER.max_data_file_bytes if max_bytes is None else max_bytes
+		with gzip_open(path, "rb") as f:
+			content = f.read(limit + 1) if limit > 0 else f.read()
+		if limit > 0 and len(content) > limit:
+			msg = f"文件过大: {path.name} 解压后超过 {limit} 字节"
+			raise FileTooLargeError(msg)
+		return loads(content.decode(encoding))
+
+	@staticmethod
+	def list_files(
+		directory: Path,
+		pattern: str | None = None,
+		*,
+		recursive: bool = False,
+		sort_by: Literal["path", "modified"] = "path",
+	) -> list[FileEntry]:
+		"""列出目录下匹配通配符的文件, 跳过无法读取的条目"""
+		entries: list[FileEntry] = []
+		visited: set[tuple[int, int]] = set()
+		pending = [directory]
+		while pending:
+			current = pending.pop()
+			try:
+				stat = current.stat()
+				# 记录已访问目录, 防止符号链接成环
+				if (stat.st_dev, stat.st_ino) in visited:
+					continue
+				visited.add((stat.st_dev, stat.st_ino))
+				children = list(current.iterdir())
+			except OSError as e:
+				print(f"Warning: 无法读取目录 {current}: {e}")
+				continue
+			for child in children:
+				try:
+					if child.is_dir():
+						if recursive:
+							pending.append(child)
+						continue
+					child_stat = child.stat()
+				except OSError as e:
+					print(f"Warning: 无法读取文件 {child}: {e}")
+					continue
+				relative = child.relative_to(directory).as_posix()
+				if pattern is not None and not (fnmatch(relative, pattern) or fnmatch(child.name, pattern)):
+					continue
+				entries.append(FileEntry(modified=child_stat.st_mtime, path=child, size=child_stat.st_size))
+		if sort_by == "modified":
+			entries.sort(key=lambda entry: (entry.modified, entry.path.as_posix()))
+		else:
+			entries.sort(key=lambda entry: entry.path.as_posix())
+		return entries
+
+
+@decorator.singleton_fallible
 class DataManager(BaseManager[CodeMaoData]):
 	def __init__(self) -> None:
-		super().__init__(file_path=PathConfig.DATA_FILE_PATH, data_class=CodeMaoData)
+		super().__init__(file_path=lambda: PathConfig.DATA_FILE_PATH, data_class=CodeMaoData)
+		# 密码当前是否保存在系统钥匙串中, 为 True 时 data.json 只写入标记
+		self._password_in_keyring = False
+
+	@property
+	def data(self) -> CodeMaoData:
+		"""获取数据实例, 启用 normalize_user_data 时加载后自动规范化, 密码为钥匙串标记时从钥匙串读取"""
+		if self._data is None:
+			self._data = JsonFileHandler.load_json_file(self._file_path, self._data_class)
+			self._resolve_password(self._data.ACCOUNT_DATA)
+			if SettingManager().data.PARAMETER.normalize_user_data:
+				self._normalize_lists(self._data.USER_DATA)
+		return self._data
 
+	def save(self) -> None:
+		"""保存数据到文件, 启用 normalize_user_data 时先规范化, 使用钥匙串时先写入密码"""
+		if SettingManager().data.PARAMETER.normalize_user_data:
+			self._normalize_lists(self.data.USER_DATA)
+		if not BaseManager.is_safe_mode():
+			self._store_password()
+		super().save()
 
-@decorator.singleton
+	@staticmethod
+	def _credential_label(account: AccountData) -> str:
+		"""钥匙串中的用户名, 优先使用登录账号"""
+		return account.identity or (str(account.id) if account.id else "")
+
+	def _resolve_password(self, account: AccountData) -> None:
+		"""将钥匙串标记替换为实际密码, 读取失败时保留标记, 避免保存时丢失"""
+		self._password_in_keyring = False
+		if account.password != KEYRING_MARKER:
+			return
+		backend = CredentialVault.backend()
+		label = self._credential_label(account)
+		secret = backend.get_password(KEYRING_SERVICE, label) if backend and label else None
+		if secret is None:
+			print(f"Warning: 无法从系统钥匙串读取账号 {label or '(未知)'} 的密码")
+			return
+		account.password = secret
+		self._password_in_keyring = True
+
+	def _store_password(self) -> None:
+		"""credential_store 为 keyring 时将密码写入钥匙串, 钥匙串不可用时回退到文件"""
+		account = self.data.ACCOUNT_DATA
+		if account.password == KEYRING_MARKER:
+			return
+		self._password_in_keyring = False
+		if not account.password or SettingManager().data.PARAMETER.credential_store != "keyring":
+			return
+		backend = CredentialVault.backend()
+		label = self._credential_label(account)
+		if backend is None:
+			CredentialVault.warn_unavailable()
+			return
+		if not label:
+			print("Warning: 账号缺少 identity 与 id, 密码仍保存在 data.json 中")
+			return
+		try:
+			backend.set_password(KEYRING_SERVICE, label, account.password)
+		except Exception as e:
+			print(f"Warning: 写入系统钥匙串失败, 密码仍保存在 data.json 中: {e}")
+			return
+		self._password_in_keyring = True
+
+	def _data_for_file(self) -> CodeMaoData:
+		if not self._password_in_keyring:
+			return self.data
+		return replace(self.data, ACCOUNT_DATA=replace(self.data.ACCOUNT_DATA, password=KEYRING_MARKER))
+
+	def migrate_credentials(self, to: CredentialStore) -> None:
+		"""在 data.json 与系统钥匙串之间迁移密码, 同时更新配置中的 credential_store"""
+		if to not in get_args(CredentialStore):
+			msg = f"未知的凭据存储 {to!r}, 可选值: {get_args(CredentialStore)}"
+			raise ValueError(msg)
+		if BaseManager.is_safe_mode():
+			print(f"[safe_mode] 跳过迁移凭据到 {to}")
+			return
+		if to == "keyring" and not CredentialVault.available():
+			msg = "没有可用的系统钥匙串, 无法迁移凭据"
+			raise RuntimeError(msg)
+		with self._lock:
+			account = self.data.ACCOUNT_DATA
+			if account.password == KEYRING_MARKER:
+				msg = "密码尚未从系统钥匙串读取, 无法迁移"
+				raise RuntimeError(msg)
+			was_in_keyring = self._password_in_keyring
+			SettingManager().update_with(lambda setting: setattr(setting.PARAMETER, "credential_store", to))
+			self.save()
+			backend = CredentialVault.backend()
+			if to == "file" and was_in_keyring and backend is not None:
+				try:
+					backend.delete_password(KEYRING_SERVICE, self._credential_label(account))
+				except Exception as e:
+					print(f"Warning: 删除系统钥匙串中的密码失败: {e}")
+
+	def normalize(self, *, dry_run: bool = False) -> dict[str, tuple[list[str], list[str]]]:
+		"""
+		规范化 USER_DATA 列表: 去除首尾空白、删除空项、保持首次出现顺序去重, ads 和 black_room 额外排序
+		black_room 按 user_id、comments 和 replies 按 text 处理, 返回发生变化的字段及其变化前后的值 (对应为 user_id 或 text 列表), dry_run 时只报告不修改
+		"""
+		with self._lock:
+			if dry_run:
+				snapshot = UserData(**{name: list(getattr(self.data.USER_DATA, name)) for name in USER_DATA_LIST_FIELDS})
+				return self._normalize_lists(snapshot)
+			changes = self._normalize_lists(self.data.USER_DATA)
+			if changes:
+				super().save()
+			return changes
+
+	@staticmethod
+	def _normalize_lists(user_data: UserData) -> dict[str, tuple[list[str], list[str]]]:
+		"""就地规范化列表字段, 返回发生变化的字段"""
+		changes: dict[str, tuple[list[str], list[str]]] = {}
+		for name in USER_DATA_LIST_FIELDS:
+			if name == "black_room":
+				if black_room_change := DataManager._normalize_black_room(user_data):
+					changes[name] = black_room_change
+				continue
+			if name in USER_DATA_TEMPLATE_FIELDS:
+				if template_change := DataManager._normalize_templates(user_data, name):
+					changes[name] = template_change
+				continue
+			before: list[str] = getattr(user_data, name)
+			after = tool.DataProcessor().deduplicate(item.strip() for item in before if isinstance(item, str) and item.strip())
+			if name in USER_DATA_SORTED_FIELDS:
+				after.sort()
+			if after != before:
+				changes[name] = (list(before), after)
+				setattr(user_data, name, after)
+		return changes
+
+	@staticmethod
+	def _normalize_black_room(user_data: UserData) -> tuple[list[str], list[str]] | None:
+		"""按 user_id 去除首尾空白、删除空项并去重 (保留首次出现的条目), 再按 user_id 排序"""
+		before = [entry.user_id for entry in user_data.black_room]
+		entries: dict[str, BlackRoomEntry] = {}
+		for entry in user_data.black_room:
+			user_id = entry.user_id.strip()
+			if user_id and user_id not in entries:
+				entries[user_id] = replace(entry, user_id=user_id)
+		after = sorted(entries)
+		if after == before:
+			return None
+		user_data.black_room = [entries[user_id] for user_id in after]
+		return before, after
+
+	@staticmethod
+	def _normalize_templates(user_data: UserData, name: str) -> tuple[list[str], list[str]] | None:
+		"""按 text 去除首尾空白、删除空项并去重 (保留首次出现的模板及其元数据), 不改变顺序"""
+		templates: list[CommentTemplate] = getattr(user_data, name)
+		before = [template.text for template in templates]
+		seen: dict[str, CommentTemplate] = {}
+		for template in templates:
+			text = template.text.strip()
+			if text and text not in seen:
+				seen[text] = replace(template, text=text)
+		after = list(seen)
+		if after == before:
+			return None
+		setattr(user_data, name, list(seen.values()))
+		return before, after
+
+	def add_to_black_room(self, entry: BlackRoomEntry) -> bool:
+		"""添加黑名单条目, user_id 已存在时不重复添加, 返回是否添加; 未设置 added_at 时使用当前时间"""
+		user_id = entry.user_id.strip()
+		if not user_id:
+			msg = "黑名单条目缺少 user_id"
+			raise ValueError(msg)
+		with self._lock:
+			if self.is_blacklisted(user_id):
+				return False
+			self.update_with(lambda data: data.USER_DATA.black_room.append(replace(entry, added_at=entry.added_at or int(time()), user_id=user_id)))
+			return True
+
+	def remove_from_black_room(self, user_id: str | int) -> bool:
+		"""移除黑名单条目, 返回是否存在该用户"""
+		user_id = str(user_id).strip()
+		with self._lock:
+			if not self.is_blacklisted(user_id):
+				return False
+
+			def remove(data: CodeMaoData) -> None:
+				data.USER_DATA.black_room = [entry for entry in data.USER_DATA.black_room if entry.user_id != user_id]
+
+			self.update_with(remove)
+			return True
+
+	def is_blacklisted(self, user_id: str | int) -> bool:
+		"""判断用户是否在黑名单中"""
+		user_id = str(user_id).strip()
+		return self.read_with(lambda data: any(entry.user_id == user_id for entry in data.USER_DATA.black_room))
+
+	def blacklisted_ids(self) -> set[str]:
+		"""黑名单中全部用户 ID, 供批量检查使用"""
+		return self.read_with(lambda data: {entry.user_id for entry in data.USER_DATA.black_room})
+
+
+@decorator.singleton_fallible
 class CacheManager(BaseManager[CodeMaoCache]):
 	def __init__(self) -> None:
-		super().__init__(file_path=PathConfig.CACHE_FILE_PATH, data_class=CodeMaoCache)
+		super().__init__(file_path=lambda: PathConfig.CACHE_FILE_PATH, data_class=CodeMaoCache)
+		# 尚未写入的使用次数, 键为 (日期, 计数器名)
+		self._pending_usage: dict[tuple[str, str], int] = {}
+		self._usage_debouncer = decorator.Debouncer(USAGE_FLUSH_SECONDS)
+
+	def increment(self, counter: str, by: int = 1, now: float | None = None) -> None:
+		"""
+		使用次数加 by, 未开启 usage_stats 时不做任何事
+		统计只保存在本地 cache.json 中, 不会通过网络发送; 计数先在内存中累积, 由防抖定时器统一写入, 可在频繁调用的路径中使用
+		"""
+		if not SettingManager().data.PARAMETER.usage_stats:
+			return
+		key = (strftime("%Y-%m-%d", localtime(now)), counter)
+		with self._lock:
+			schedule = not self._pending_usage
+			self._pending_usage[key] = self._pending_usage.get(key, 0) + by
+		# 只在有新的待写入计数时安排一次写入, 避免每次计数都重新创建定时器
+		if schedule:
+			self._usage_debouncer.call(self.flush_usage)
+
+	def flush_usage(self) -> None:
+		"""立即写入内存中累积的使用次数, 程序退出前应调用"""
+		with self._lock:
+			if not self._pending_usage:
+				return
+			self._merge_usage(self.data.usage, self._pending_usage)
+			self._pending_usage = {}
+			self.save()
+
+	def usage_summary(self, period: UsagePeriod = "all", now: float | None = None) -> dict[str, int]:
+		"""汇总当天、当月、当年或全部的使用次数 (包括尚未写入的计数), 只读取本地数据"""
+		prefix = {"day": "%Y-%m-%d", "month": "%Y-%m", "year": "%Y", "all": ""}[period]
+		prefix = strftime(prefix, localtime(now)) if prefix else ""
+		with self._lock:
+			usage = {day: dict(counters) for day, counters in self.data.usage.items()}
+			self._merge_usage(usage, self._pending_usage)
+		summary: dict[str, int] = {}
+		for day, counters in usage.items():
+			if day.startswith(prefix):
+				for counter, count in counters.items():
+					summary[counter] = summary.get(counter, 0) + count
+		return summary
 
+	@staticmethod
+	def _merge_usage(usage: dict[str, dict[str, int]], pending: Mapping[tuple[str, str], int]) -> None:
+		for (day, counter), count in pending.items():
+			counters = usage.setdefault(day, {})
+			counters[counter] = counters.get(counter, 0) + count
+
+	def get_fresh(self, refresher: Callable[[], dict[str, Any]], max_age: float | None = None, now: Callable[[], float] = time) -> CodeMaoCache:
+		"""
+		缓存未过期时直接返回, 否则调用 refresher 获取新数据并写入缓存
+		max_age 缺省取配置中的 cache_ttl_minutes, 并发调用时只会刷新一次
+		"""
+		if max_age is None:
+			max_age = SettingManager().data.PARAMETER.cache_ttl_minutes * 60
+		with self._lock:
+			if not self.data.is_stale(max_age, now()):
+				return self.data
+			# 复制一份再写入时间戳, 避免修改 refresher 返回的字典
+			self.update({**refresher(), "timestamp": int(now())})
+			return self.data
+
+
+def _restore_overridden(current: Mapping[str, Any], base: Mapping[str, Any], overrides: Mapping[str, Any]) -> dict[str, Any]:
+	"""将 current 中被档案覆盖的配置项还原为 base 中的值"""
+	result = dict(current)
+	for key, value in overrides.items():
+		if isinstance(value, dict) and isinstance(result.get(key), dict) and isinstance(base.get(key), dict):
+			result[key] = _restore_overridden(result[key], base[key], value)
+		elif key in base:
+			result[key] = base[key]
+		else:
+			result.pop(key, None)
+	return result
 
-@decorator.singleton
+
+@decorator.singleton_fallible
 class SettingManager(BaseManager[CodeMaoSetting]):
-	def __init__(self) -> None:
-		super().__init__(file_path=PathConfig.SETTING_FILE_PATH, data_class=CodeMaoSetting)
+	def __init__(self, file_path: Path | None = None) -> None:
+		super().__init__(file_path=file_path or (lambda: PathConfig.SETTING_FILE_PATH), data_class=CodeMaoSetting)
+		self._hot_reload = False
+		self._last_mtime = 0.0
+		self._reload_listeners: list[Callable[[CodeMaoSetting], None]] = []
+		self._profile = environ.get(PROFILE_ENV_VAR) or DEFAULT_PROFILE
+		# 当前档案的覆盖项及 setting.json 中的原始配置, 保存时覆盖项写回原始值, 避免档案内容混入 setting.json
+		self._profile_overrides: dict[str, Any] = {}
+		self._base: dict[str, Any] = {}
 
+	@property
+	def data(self) -> CodeMaoSetting:
+		"""获取配置实例 (已合并当前档案), 启用热重载时先检查文件是否变更; 档案不存在时抛出 ProfileError"""
+		if self._hot_reload:
+			self._reload_if_changed()
+		if self._data is None:
+			base = JsonFileHandler.load_json_file(self._file_path, CodeMaoSetting)
+			self._store(base, *self._apply_profile(base, self._profile))
+		return cast("CodeMaoSetting", self._data)
+
+	def active_profile(self) -> str:
+		"""当前使用的配置档案名称"""
+		return self._profile
+
+	def profile_path(self, name: str) -> Path:
+		"""配置档案文件路径, 与 setting.json 位于同一目录, 如 setting.testing.json"""
+		return self._file_path.with_name(f"{self._file_path.stem}.{name}{self._file_path.suffix}")
+
+	def use_profile(self, name: str) -> None:
+		"""
+		切换配置档案, 档案中的配置项递归合并到 setting.json 上 (列表整体替换)
+		与热重载一样先校验合并后的配置, 通过后才替换并通知监听器; 失败时抛出 ProfileError, 原配置不变
+		"""
+		with self._lock:
+			base = JsonFileHandler.load_json_file(self._file_path, CodeMaoSetting)
+			candidate, overrides = self._apply_profile(base, name)
+			errors = SettingValidator.validate(candidate)
+			if errors:
+				details = "; ".join(f"{error.path}: {error.message}" for error in errors)
+				msg = f"配置档案 {name} 校验未通过: {details}"
+				raise ProfileError(msg)
+			self._profile = name
+			self._store(base, candidate, overrides)
+			self._last_mtime = self._current_mtime()
+			listeners = list(self._reload_listeners)
+		print(f"已切换到配置档案 {name}")
+		self._notify(listeners, candidate)
+
+	def _read_profile(self, name: str) -> dict[str, Any]:
+		"""读取档案中的覆盖项, default 档案没有覆盖项"""
+		if name == DEFAULT_PROFILE:
+			return {}
+		if not PROFILE_NAME_PATTERN.match(name):
+			msg = f"配置档案名称 {name!r} 无效, 只能包含字母、数字、下划线和连字符"
+			raise ProfileError(msg)
+		path = self.profile_path(name)
+		try:
+			with path.open(encoding="utf-8-sig") as f:
+				overrides = load(f)
+		except FileNotFoundError as e:
+			msg = f"配置档案 {name} 不存在, 请先创建 {path}"
+			raise ProfileError(msg) from e
+		except (OSError, JSONDecodeError) as e:
+			msg = f"无法读取配置档案 {path}: {e}"
+			raise ProfileError(msg) from e
+		if not isinstance(overrides, dict):
+			msg = f"配置档案 {path} 的内容应为 JSON 对象"
+			raise ProfileError(msg)
+		return overrides
+
+	def _apply_profile(self, base: CodeMaoSetting, name: str) -> tuple[CodeMaoSetting, dict[str, Any]]:
+		"""返回合并档案后的配置及档案的覆盖项"""
+		overrides = self._read_profile(name)
+		if not overrides:
+			return base, overrides
+		merged = tool.DataProcessor().deep_merge(asdict(base), overrides)
+		return DataClassConverter.dict_to_dataclass(CodeMaoSetting, merged), overrides
+
+	def _store(self, base: CodeMaoSetting, candidate: CodeMaoSetting, overrides: dict[str, Any]) -> None:
+		self._data = candidate
+		self._profile_overrides = overrides
+		self._base = asdict(base) if overrides else {}
+
+	def _data_for_file(self) -> CodeMaoSetting:
+		"""使用档案时, 被档案覆盖的配置项按 setting.json 中原有的值写入"""
+		if not self._profile_overrides:
+			return self.data
+		restored = _restore_overridden(asdict(self.data), self._base, self._profile_overrides)
+		return DataClassConverter.dict_to_dataclass(CodeMaoSetting, restored)
+
+	def enable_hot_reload(self) -> None:
+		"""启用热重载, 之后每次访问配置都会检查 setting.json 及当前档案文件是否被修改"""
+		self._hot_reload = True
+		self._last_mtime = self._current_mtime()
+
+	def add_reload_listener(self, listener: Callable[[CodeMaoSetting], None]) -> None:
+		"""注册配置重载后的回调"""
+		self._reload_listeners.append(listener)
+
+	def _current_mtime(self) -> float:
+		"""获取配置文件修改时间, 使用档案时取 setting.json 与档案文件中较新者"""
+		paths = [self._file_path]
+		if self._profile != DEFAULT_PROFILE:
+			paths.append(self.profile_path(self._profile))
+		mtimes = [0.0]
+		for path in paths:
+			try:
+				mtimes.append(path.stat().st_mtime)
+			except OSError:
+				continue
+		return max(mtimes)
+
+	def _reload_if_changed(self) -> None:
+		"""
+		配置文件变更时重新解析并校验, 仅在校验通过后替换内存中的配置
+		校验包含请求头的环境变量替换, 通过后监听器不会因配置本身出错; 单个监听器出错不影响其他监听器
+		"""
+		with self._lock:
+			mtime = self._current_mtime()
+			if mtime == self._last_mtime:
+				return
+			self._last_mtime = mtime
+			try:
+				with self._file_path.open(encoding="utf-8-sig") as f:
+					base = DataClassConverter.dict_to_dataclass(CodeMaoSetting, load(f))
+				candidate, overrides = self._apply_profile(base, self._profile)
+			except (OSError, JSONDecodeError, ValueError, TypeError) as e:
+				print(f"Error: 重新加载 {self._file_path.name} 失败, 继续使用原配置: {e}")
+				return
+			errors = SettingValidator.validate(candidate)
+			if errors:
+				for error in errors:
+					print(f"Error: {self._file_path.name} 配置项 {error.path} 无效: {error.message}")
+				print(f"{self._file_path.name} 校验未通过, 继续使用原配置")
+				return
+			self._store(base, candidate, overrides)
+			listeners = list(self._reload_listeners)
+		print(f"已重新加载 {self._file_path.name} (配置档案: {self._profile})")
+		self._notify(listeners, candidate)
 
-@decorator.singleton
+	@staticmethod
+	def _notify(listeners: list[Callable[[CodeMaoSetting], None]], setting: CodeMaoSetting) -> None:
+		for listener in listeners:
+			try:
+				listener(setting)
+			except Exception as e:
+				print(f"Error: 配置重载回调出错: {e}")
+
+	def regenerate(self) -> Path | None:
+		"""备份现有 setting.json 后用默认配置重新生成, 返回备份文件路径; 安全模式下不做任何改动, 返回 None"""
+		if JsonFileHandler._writes_blocked(self._file_path):  # noqa: SLF001
+			print(f"[safe_mode] 跳过重新生成 {self._file_path.name}")
+			return None
+		backup_path = None
+		if self._file_path.exists():
+			backup_path = self._file_path.with_name(f"{self._file_path.name}.bak-{int(time())}")
+			copy2(self._file_path, backup_path)
+			print(f"原配置已备份到 {backup_path}")
+		instance = DataClassConverter.dict_to_dataclass(CodeMaoSetting, DEFAULT_SETTING_DATA)
+		JsonFileHandler.save_json_file(self._file_path, instance)
+		self._store(instance, *self._apply_profile(instance, self._profile))
+		print(f"已重新生成默认配置 {self._file_path}")
+		return backup_path
+
+	def validate(self) -> list[SettingError]:
+		"""
+		校验 setting.json 与当前档案合并后的配置, 打印未知配置项警告后返回错误列表, 由调用方决定如何报告错误
+		档案不存在时抛出 ProfileError
+		"""
+		try:
+			with self._file_path.open(encoding="utf-8-sig") as f:
+				raw = load(f)
+		except (OSError, JSONDecodeError):
+			raw = {}
+		sources = [(self._file_path.name, raw), (self.profile_path(self._profile).name, self._read_profile(self._profile))]
+		for name, content in sources:
+			if isinstance(content, dict):
+				for path in SettingValidator.find_unknown_keys(CodeMaoSetting, content):
+					print(f"Warning: {name} 中存在未知配置项 {path}, 可能是拼写错误")
+		return SettingValidator.validate(self.data)
+
+
+@decorator.singleton_fallible
 class HistoryManager(BaseManager[CodemaoHistory]):
 	def __init__(self) -> None:
-		super().__init__(file_path=PathConfig.HISTORY_FILE_PATH, data_class=CodemaoHistory)
+		super().__init__(file_path=lambda: PathConfig.HISTORY_FILE_PATH, data_class=CodemaoHistory)
+
+
+@decorator.singleton_fallible
+class CacheHistoryManager:
+	"""缓存快照历史管理器, 以 JSON Lines 格式追加保存"""
+
+	# 读取最后一条快照时从文件末尾读取的字节数
+	TAIL_BYTES = 64 * 1024
+
+	def __init__(self, max_entries: int = 1000, max_age_days: int = 365, file_path: Path | None = None) -> None:
+		self._custom_path = file_path
+		# 文件中的快照条数, 首次追加时统计
+		self._line_count: int | None = None
+		self.max_entries = max_entries
+		self.max_age_days = max_age_days
+
+	@property
+	def _file_path(self) -> Path:
+		return self._custom_path or PathConfig.CACHE_HISTORY_FILE_PATH
+
+	def _load(self) -> list[CodeMaoCache]:
+		"""读取全部快照, 跳过无法解析的行"""
+		if not self._file_path.exists():
+			return []
+		snapshots: list[CodeMaoCache] = []
+		with self._file_path.open(encoding="utf-8") as f:
+			for line_number, line in enumerate(f, 1):
+				if not line.strip():
+					continue
+				try:
+					snapshots.append(DataClassConverter.dict_to_dataclass(CodeMaoCache, loads(line)))
+				except (JSONDecodeError, ValueError, TypeError) as e:
+					print(f"Warning: {self._file_path.name} 第 {line_number} 行无法解析: {e}")
+		return snapshots
+
+	def _save(self, snapshots: list[CodeMaoCache]) -> None:
+		"""覆盖写入全部快照"""
+		if BaseManager.is_safe_mode():
+			print(f"[safe_mode] 跳过写入 {self._file_path.name}")
+			return
+		CodeMaoFile.file_write(self._file_path, [dumps(asdict(snapshot), ensure_ascii=False) for snapshot in snapshots])
+
+	@staticmethod
+	def _parse_line(line: str) -> CodeMaoCache | None:
+		try:
+			return DataClassConverter.dict_to_dataclass(CodeMaoCache, loads(line))
+		except (JSONDecodeError, ValueError, TypeError):
+			return None
+
+	def _first_snapshot(self) -> CodeMaoCache | None:
+		"""读取第一条快照, 只读取文件开头"""
+		with self._file_path.open(encoding="utf-8") as f:
+			for line in f:
+				if line.strip():
+					return self._parse_line(line)
+		return None
+
+	def _last_snapshot(self) -> CodeMaoCache | None:
+		"""读取最后一条快照, 只读取文件末尾"""
+		if not self._file_path.exists():
+			return None
+		with self._file_path.open("rb") as f:
+			f.seek(0, SEEK_END)
+			f.seek(max(0, f.tell() - self.TAIL_BYTES))
+			tail = f.read().decode("utf-8", errors="ignore")
+		for line in reversed(tail.splitlines()):
+			if line.strip():
+				return self._parse_line(line)
+		return None
+
+	def _count_lines(self) -> int:
+		with self._file_path.open(encoding="utf-8") as f:
+			return sum(1 for line in f if line.strip())
+
+	def _prune(self, snapshots: list[CodeMaoCache]) -> list[CodeMaoCache]:
+		"""按条数与时间裁剪历史"""
+		if self.max_age_days > 0 and snapshots:
+			cutoff = snapshots[-1].timestamp - self.max_age_days * 86400
+			snapshots = [snapshot for snapshot in snapshots if snapshot.timestamp >= cutoff]
+		if self.max_entries > 0:
+			snapshots = snapshots[-self.max_entries :]
+		return snapshots
+
+	def _exceeds_limits(self, latest: CodeMaoCache) -> bool:
+		"""判断追加后是否超出条数或时间限制"""
+		if self.max_entries > 0 and self._line_count is not None and self._line_count > self.max_entries:
+			return True
+		if self.max_age_days > 0:
+			first = self._first_snapshot()
+			return first is not None and first.timestamp < latest.timestamp - self.max_age_days * 86400
+		return False
+
+	def append(self, snapshot: CodeMaoCache) -> bool:
+		"""追加快照, 与上一条内容相同 (忽略时间戳) 时跳过, 返回是否写入; 只追加一行, 超出限制时才重写文件"""
+		# 使用统计单独累计, 不随快照重复保存
+		snapshot = replace(snapshot, usage={})
+		last = self._last_snapshot()
+		if last is not None and replace(last, timestamp=0) == replace(snapshot, timestamp=0):
+			return False
+		if BaseManager.is_safe_mode():
+			print(f"[safe_mode] 跳过写入 {self._file_path.name}")
+			return False
+		CodeMaoFile.file_write(self._file_path, [dumps(asdict(snapshot), ensure_ascii=False)], method="a")
+		self._line_count = self._count_lines() if self._line_count is None else self._line_count + 1
+		if self._exceeds_limits(snapshot):
+			snapshots = self._prune(self._load())
+			self._save(snapshots)
+			self._line_count = len(snapshots)
+		return True
+
+	def history(self, start: int | None = None, end: int | None = None) -> list[CodeMaoCache]:
+		"""获取时间范围 [start, end] 内的快照"""
+		return [snapshot for snapshot in self._load() if (start is None or snapshot.timestamp >= start) and (end is None or snapshot.timestamp <= end)]
+
+	def latest_before(self, timestamp: int) -> CodeMaoCache | None:
+		"""获取指定时间之前的最后一条快照"""
+		candidates = self.history(end=timestamp)
+		return candidates[-1] if candidates else None
+
+	def report(self, start: int | None = None, end: int | None = None) -> bool:
+		"""对比时间范围内首尾两条快照, 返回是否有足够数据"""
+		snapshots = self.history(start, end)
+		if len(snapshots) < 2:
+			print("历史快照不足, 无法生成对比报告")
+			return False
+		tool.DataAnalyzer().compare_datasets(
+			before=snapshots[0],
+			after=snapshots[-1],
+			metrics={"fans": "粉丝", "collected": "被收藏", "liked": "被赞", "view": "被预览"},
+			timestamp_field="timestamp",
+		)
+		return True
 
 
 class NestedDefaultDict(UserDict[str, Any]):
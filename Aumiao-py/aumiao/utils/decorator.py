@@ -1,20 +1,69 @@
-from collections.abc import Callable, Generator
+from collections import OrderedDict, deque
+from collections.abc import Callable, Generator, Hashable, Iterable, Sized
+from concurrent.futures import Future, ThreadPoolExecutor, wait
+from contextlib import contextmanager
+from dataclasses import dataclass, field
 from functools import lru_cache, wraps
+from random import Random
+from threading import Condition, Event, Lock, RLock, Thread, Timer
+from time import monotonic, perf_counter, sleep
+from typing import Literal, Self, cast
 
 
 def singleton(cls):  # noqa: ANN001, ANN201
 	instances = {}
+	# 双重检查加锁, 避免多线程同时首次访问时创建多个实例
+	lock = RLock()
 
 	@wraps(cls)
 	def wrapper(*args, **kwargs):  # noqa: ANN002, ANN003, ANN202
 		if cls not in instances:
-			instances[cls] = cls(*args, **kwargs)
+			with lock:
+				if cls not in instances:
+					instances[cls] = cls(*args, **kwargs)
 		return instances[cls]
 
 	wrapper.__dict__.update(cls.__dict__)
 	return wrapper
 
 
+class SingletonInitError(RuntimeError):
+	"""单例首次构造失败, 后续访问均抛出此异常, 原始异常可通过 __cause__ 获取"""
+
+
+def singleton_fallible(cls):  # noqa: ANN001, ANN201
+	"""
+	适用于构造可能失败的单例: 首次构造失败时记住该异常, 之后的访问不再重复执行构造函数
+	而是直接抛出包含原始错误信息的 SingletonInitError; 可通过 init_error() 查询失败原因
+	"""
+	instances = {}
+	errors: dict[type, Exception] = {}
+	lock = RLock()
+
+	def raise_init_error() -> None:
+		error = errors[cls]
+		msg = f"{cls.__name__} 初始化失败: {error}"
+		raise SingletonInitError(msg) from error
+
+	@wraps(cls)
+	def wrapper(*args, **kwargs):  # noqa: ANN002, ANN003, ANN202
+		if cls not in instances:
+			with lock:
+				if cls in errors:
+					raise_init_error()
+				if cls not in instances:
+					try:
+						instances[cls] = cls(*args, **kwargs)
+					except Exception as e:
+						errors[cls] = e
+						raise_init_error()
+		return instances[cls]
+
+	wrapper.__dict__.update(cls.__dict__)
+	wrapper.init_error = lambda: errors.get(cls)
+	return wrapper
+
+
 def skip_on_error(func):  # noqa: ANN001, ANN201
 	@wraps(func)
 	def wrapper(*args, **kwargs):  # noqa: ANN002, ANN003, ANN202
@@ -27,6 +76,55 @@ def skip_on_error(func):  # noqa: ANN001, ANN201
 	return wrapper
 
 
+@dataclass
+class ErrorRecord:
+	context: str = ""
+	error: Exception | None = None
+
+
+class ErrorSink:
+	"""
+	线程安全的错误收集器, 与 skip_on_error 一样跳过失败项, 但保留错误以便批处理结束后汇总
+	同一实例可在多个线程间共享
+	"""
+
+	def __init__(self) -> None:
+		self._records: list[ErrorRecord] = []
+		self._lock = Lock()
+
+	def record(self, context: str, error: Exception) -> None:
+		"""记录一条错误"""
+		with self._lock:
+			self._records.append(ErrorRecord(context=context, error=error))
+
+	def call[R](self, context: str, func: Callable[..., R], *args, **kwargs) -> R | None:  # noqa: ANN002, ANN003
+		"""执行 func, 出错时记录错误并返回 None"""
+		try:
+			return func(*args, **kwargs)
+		except Exception as e:
+			print(f"Error occurred: {e}. Skipping {context}.")
+			self.record(context, e)
+			return None
+
+	def summary(self, classify: Callable[[Exception], str] | None = None) -> dict[str, int]:
+		"""按分类统计错误数, 默认按异常类型名分类"""
+		classify = classify or (lambda error: type(error).__name__)
+		counts: dict[str, int] = {}
+		for record in self.records():
+			key = classify(cast("Exception", record.error))
+			counts[key] = counts.get(key, 0) + 1
+		return counts
+
+	def records(self) -> list[ErrorRecord]:
+		"""返回全部错误明细的副本"""
+		with self._lock:
+			return list(self._records)
+
+	def __len__(self) -> int:
+		with self._lock:
+			return len(self._records)
+
+
 def generator(chunk_size: int = 1000) -> Callable:
 	# 定义一个装饰器函数, 用于将一个函数的返回值按指定大小分割成多个块
 	def decorator(func: Callable) -> Callable:
@@ -74,3 +172,681 @@ def lru_cache_with_reset(maxsize: int = 128, max_calls: int = 3, *, typed: bool
 		return wrapper
 
 	return decorator
+
+
+class RateLimiter:
+	"""
+	线程安全的限流器, 每个 interval 秒内最多放行 permits_per_interval 次
+	等待者按到达顺序依次放行, 可直接作为装饰器使用
+	"""
+
+	def __init__(self, permits_per_interval: int, interval: float) -> None:
+		if permits_per_interval <= 0 or interval <= 0:
+			msg = "permits_per_interval 和 interval 必须为正数"
+			raise ValueError(msg)
+		self._permits = permits_per_interval
+		self._interval = interval
+		self._tokens = permits_per_interval
+		self._window_start = monotonic()
+		self._condition = Condition()
+		# 排队号, 保证先到先得
+		self._next_ticket = 0
+		self._serving = 0
+
+	def _refill(self) -> None:
+		"""进入新的时间窗口时补满令牌"""
+		elapsed = monotonic() - self._window_start
+		if elapsed >= self._interval:
+			self._window_start += (elapsed // self._interval) * self._interval
+			self._tokens = self._permits
+
+	def try_acquire(self) -> bool:
+		"""非阻塞获取, 有人排队或令牌不足时返回 False"""
+		with self._condition:
+			if self._serving != self._next_ticket:
+				return False
+			self._refill()
+			if self._tokens <= 0:
+				return False
+			self._tokens -= 1
+			return True
+
+	def acquire(self) -> None:
+		"""阻塞直到获得令牌"""
+		with self._condition:
+			ticket = self._next_ticket
+			self._next_ticket += 1
+			while True:
+				if self._serving == ticket:
+					self._refill()
+					if self._tokens > 0:
+						self._tokens -= 1
+						self._serving += 1
+						self._condition.notify_all()
+						return
+					self._condition.wait(self._window_start + self._interval - monotonic())
+				else:
+					self._condition.wait()
+
+	def __call__(self, func: Callable) -> Callable:
+		@wraps(func)
+		def wrapper(*args, **kwargs):  # noqa: ANN002, ANN003, ANN202
+			self.acquire()
+			return func(*args, **kwargs)
+
+		return wrapper
+
+
+class Memoized:
+	"""
+	带过期时间的记忆化包装, 按参数缓存结果并按 LRU 淘汰
+	同一参数的并发调用只会执行一次, 其余调用等待其结果
+	"""
+
+	def __init__(self, func: Callable, ttl: float, capacity: int = 128, *, cache_errors: bool = False) -> None:
+		self._func = func
+		self._ttl = ttl
+		self._capacity = capacity
+		self._cache_errors = cache_errors
+		# 键 -> (过期时间, 是否为异常, 结果或异常)
+		self._cache: OrderedDict[Hashable, tuple[float, bool, object]] = OrderedDict()
+		self._pending: dict[Hashable, Event] = {}
+		self._lock = Lock()
+		wraps(func)(self)
+
+	@staticmethod
+	def _make_key(args: tuple, kwargs: dict) -> Hashable:
+		return (args, tuple(sorted(kwargs.items())) if kwargs else ())
+
+	def __call__(self, *args, **kwargs):  # noqa: ANN002, ANN003, ANN204
+		key = self._make_key(args, kwargs)
+		while True:
+			with self._lock:
+				cached = self._cache.get(key)
+				if cached is not None and cached[0] > monotonic():
+					self._cache.move_to_end(key)
+					_, is_error, value = cached
+					if is_error:
+						raise cast("Exception", value)
+					return value
+				pending = self._pending.get(key)
+				if pending is None:
+					pending = self._pending[key] = Event()
+					break
+			# 其他线程正在计算同一个键, 等待完成后重新读取缓存 (若其失败且不缓存异常, 则由当前线程重新执行)
+			pending.wait()
+		try:
+			result = self._func(*args, **kwargs)
+		except Exception as e:
+			if self._cache_errors:
+				self._store(key, is_error=True, value=e)
+			raise
+		else:
+			self._store(key, is_error=False, value=result)
+			return result
+		finally:
+			with self._lock:
+				self._pending.pop(key).set()
+
+	def _store(self, key: Hashable, *, is_error: bool, value: object) -> None:
+		with self._lock:
+			self._cache[key] = (monotonic() + self._ttl, is_error, value)
+			self._cache.move_to_end(key)
+			while len(self._cache) > self._capacity:
+				self._cache.popitem(last=False)
+
+	def invalidate(self, *args, **kwargs) -> None:  # noqa: ANN002, ANN003
+		"""删除指定参数的缓存"""
+		with self._lock:
+			self._cache.pop(self._make_key(args, kwargs), None)
+
+	def clear(self) -> None:
+		"""清空全部缓存"""
+		with self._lock:
+			self._cache.clear()
+
+
+def memoize(ttl: float, capacity: int = 128, *, cache_errors: bool = False) -> Callable[[Callable], Memoized]:
+	"""带过期时间的记忆化装饰器, 默认不缓存异常"""
+
+	def decorator(func: Callable) -> Memoized:
+		return Memoized(func, ttl, capacity, cache_errors=cache_errors)
+
+	return decorator
+
+
+@dataclass
+class ProgressUpdate:
+	done: int = 0
+	elapsed: float = 0.0
+	eta: float | None = None
+	total: int | None = None
+
+
+def render_progress_bar(update: ProgressUpdate, width: int = 30) -> None:
+	"""默认进度回调, 用回车符在同一行刷新终端进度条"""
+	if update.total:
+		filled = int(width * update.done / update.total)
+		bar = f"[{'#' * filled}{'-' * (width - filled)}] {update.done}/{update.total}"
+	else:
+		bar = f"已处理 {update.done}"
+	eta = f" 剩余 {update.eta:.0f}s" if update.eta is not None else ""
+	print(f"\r{bar} 用时 {update.elapsed:.0f}s{eta}", end="\n" if update.total and update.done >= update.total else "", flush=True)
+
+
+def silent_progress(_update: ProgressUpdate) -> None:
+	"""不输出任何内容的进度回调"""
+
+
+def progress[T](
+	iterable: Iterable[T],
+	total: int | None = None,
+	callback: Callable[[ProgressUpdate], None] = render_progress_bar,
+	max_updates_per_second: float = 10,
+	window: int = 20,
+) -> Generator[T]:
+	"""
+	包装可迭代对象并报告进度, 回调频率不超过 max_updates_per_second, 结束时总会回调一次
+	剩余时间根据最近 window 次回调间的平均速度估算
+	"""
+	if total is None and isinstance(iterable, Sized):
+		total = len(iterable)
+	start = last_update = monotonic()
+	samples: deque[tuple[float, int]] = deque([(start, 0)], maxlen=window)
+	min_interval = 1 / max_updates_per_second if max_updates_per_second > 0 else 0
+	done = 0
+
+	def report(now: float) -> None:
+		samples.append((now, done))
+		eta = None
+		first_time, first_done = samples[0]
+		if total is not None and done > first_done and now > first_time:
+			eta = (total - done) / ((done - first_done) / (now - first_time))
+		callback(ProgressUpdate(done=done, elapsed=now - start, eta=eta, total=total))
+
+	for item in iterable:
+		yield item
+		done += 1
+		now = monotonic()
+		if now - last_update >= min_interval:
+			last_update = now
+			report(now)
+	if last_update == start or samples[-1][1] != done:
+		report(monotonic())
+
+
+class Debouncer:
+	"""
+	防抖: 每次调用都会重新计时, 输入停止 window 秒后才执行最后一次传入的函数
+	计时器线程持有待执行的函数, 不再使用时需调用 cancel() 或以 with 语句使用, 退出时取消未执行的调用
+	"""
+
+	def __init__(self, window: float) -> None:
+		self._window = window
+		self._timer: Timer | None = None
+		self._lock = Lock()
+
+	def call(self, func: Callable[[], object]) -> None:
+		"""安排执行 func, 取消尚未执行的上一次调用"""
+		with self._lock:
+			if self._timer is not None:
+				self._timer.cancel()
+			self._timer = Timer(self._window, func)
+			self._timer.daemon = True
+			self._timer.start()
+
+	def cancel(self) -> None:
+		"""取消尚未执行的调用"""
+		with self._lock:
+			if self._timer is not None:
+				self._timer.cancel()
+				self._timer = None
+
+	def __enter__(self) -> Self:
+		return self
+
+	def __exit__(self, *_exc_info: object) -> None:
+		self.cancel()
+
+
+class Throttler:
+	"""
+	节流: 距上次执行超过 interval 秒时立即执行, 否则合并为间隔结束时的一次尾部执行
+	与 Debouncer 相同, 不再使用时需调用 cancel() 或以 with 语句使用
+	"""
+
+	def __init__(self, interval: float) -> None:
+		self._interval = interval
+		self._last_run = float("-inf")
+		self._pending: Callable[[], object] | None = None
+		self._timer: Timer | None = None
+		self._lock = Lock()
+
+	def call(self, func: Callable[[], object]) -> None:
+		"""执行或合并 func, 尾部执行使用最后一次传入的函数"""
+		with self._lock:
+			wait = self._last_run + self._interval - monotonic()
+			if wait <= 0 and self._timer is None:
+				self._last_run = monotonic()
+				run_now = True
+			else:
+				run_now = False
+				self._pending = func
+				if self._timer is None:
+					self._timer = Timer(max(wait, 0), self._run_trailing)
+					self._timer.daemon = True
+					self._timer.start()
+		if run_now:
+			func()
+
+	def _run_trailing(self) -> None:
+		with self._lock:
+			func, self._pending, self._timer = self._pending, None, None
+			self._last_run = monotonic()
+		if func is not None:
+			func()
+
+	def cancel(self) -> None:
+		"""取消尚未执行的尾部调用"""
+		with self._lock:
+			if self._timer is not None:
+				self._timer.cancel()
+			self._pending = self._timer = None
+
+	def __enter__(self) -> Self:
+		return self
+
+	def __exit__(self, *_exc_info: object) -> None:
+		self.cancel()
+
+
+class ConcurrencyLimiter:
+	"""限制同时执行的任务数, 同一实例可在多处共享以约束总体并发"""
+
+	def __init__(self, max_concurrency: int) -> None:
+		if max_concurrency <= 0:
+			msg = "max_concurrency 必须为正整数"
+			raise ValueError(msg)
+		self._max = max_concurrency
+		self._in_flight = 0
+		self._queued = 0
+		self._condition = Condition()
+
+	@property
+	def in_flight(self) -> int:
+		"""正在执行的任务数"""
+		return self._in_flight
+
+	@property
+	def queued(self) -> int:
+		"""等待执行的任务数"""
+		return self._queued
+
+	def run[R](self, func: Callable[..., R], *args, **kwargs) -> R:  # noqa: ANN002, ANN003
+		"""获取许可后执行 func, 无论成功或异常都会释放许可"""
+		with self._condition:
+			self._queued += 1
+			while self._in_flight >= self._max:
+				self._condition.wait()
+			self._queued -= 1
+			self._in_flight += 1
+		try:
+			return func(*args, **kwargs)
+		finally:
+			with self._condition:
+				self._in_flight -= 1
+				self._condition.notify()
+
+	def map_concurrent[T, R](self, items: Iterable[T], func: Callable[[T], R]) -> list[R | Exception]:
+		"""并发处理全部元素, 结果与输入顺序一致, 失败的元素以异常对象表示"""
+		item_list = list(items)
+		if not item_list:
+			return []
+		with ThreadPoolExecutor(max_workers=min(self._max, len(item_list))) as executor:
+			futures = [executor.submit(self.run, func, item) for item in item_list]
+		results: list[R | Exception] = []
+		for future in futures:
+			error = future.exception()
+			results.append(error if isinstance(error, Exception) else future.result())
+		return results
+
+
+type CircuitState = Literal["closed", "half_open", "open"]
+
+
+class CircuitOpenError(RuntimeError):
+	"""熔断器处于打开状态时拒绝调用, retry_in 为距离允许试探的剩余秒数"""
+
+	def __init__(self, retry_in: float) -> None:
+		super().__init__(f"熔断器已打开, {retry_in:.1f} 秒后可重试")
+		self.retry_in = retry_in
+
+
+class CircuitBreaker:
+	"""
+	线程安全的熔断器, 连续失败 failure_threshold 次后打开, cooldown 秒后放行一次试探调用
+	试探成功则关闭, 失败则重新打开; 只统计 Exception, 中断等 BaseException 不计入
+	同一实例可在多处共享, 也可直接作为装饰器使用
+	"""
+
+	def __init__(self, failure_threshold: int, cooldown: float, clock: Callable[[], float] = monotonic) -> None:
+		if failure_threshold <= 0 or cooldown < 0:
+			msg = "failure_threshold 必须为正整数, cooldown 不能为负数"
+			raise ValueError(msg)
+		self._clock = clock
+		self._cooldown = cooldown
+		self._failure_threshold = failure_threshold
+		self._failures = 0
+		self._lock = Lock()
+		self._opened_at: float | None = None
+		self._probing = False
+
+	@property
+	def state(self) -> CircuitState:
+		"""当前状态"""
+		with self._lock:
+			return self._current_state()
+
+	def _current_state(self) -> CircuitState:
+		if self._opened_at is None:
+			return "closed"
+		if self._probing or self._clock() - self._opened_at >= self._cooldown:
+			return "half_open"
+		return "open"
+
+	def _before_call(self) -> bool:
+		"""检查是否放行, 返回本次调用是否为试探调用"""
+		with self._lock:
+			state = self._current_state()
+			if state == "closed":
+				return False
+			if state == "open":
+				raise CircuitOpenError(cast("float", self._opened_at) + self._cooldown - self._clock())
+			if self._probing:
+				# 已有试探调用在进行中
+				raise CircuitOpenError(0.0)
+			self._probing = True
+			return True
+
+	def _on_success(self) -> None:
+		with self._lock:
+			self._failures = 0
+			self._opened_at = None
+			self._probing = False
+
+	def _on_failure(self, *, probe: bool) -> None:
+		with self._lock:
+			self._probing = False
+			self._failures += 1
+			if probe or self._failures >= self._failure_threshold:
+				self._opened_at = self._clock()
+
+	def call[R](self, func: Callable[..., R], *args, **kwargs) -> R:  # noqa: ANN002, ANN003
+		"""通过熔断器执行 func, 打开时抛出 CircuitOpenError, func 自身的异常原样抛出"""
+		probe = self._before_call()
+		try:
+			result = func(*args, **kwargs)
+		except Exception:
+			self._on_failure(probe=probe)
+			raise
+		except BaseException:
+			# 被中断的调用不计入成败, 只释放试探名额
+			with self._lock:
+				self._probing = False
+			raise
+		self._on_success()
+		return result
+
+	def __call__(self, func: Callable) -> Callable:
+		@wraps(func)
+		def wrapper(*args, **kwargs):  # noqa: ANN002, ANN003, ANN202
+			return self.call(func, *args, **kwargs)
+
+		return wrapper
+
+
+class RetryBudget:
+	"""
+	线程安全的重试预算, 每次重试消耗一个许可, 每次成功调用补充 ratio 个许可 (上限 max_permits)
+	接口降级时预算很快耗尽, 调用方应放弃重试而不是继续等待, 避免并发任务叠加成重试风暴
+	同一实例可在访问同一主机的多个客户端间共享
+	"""
+
+	def __init__(self, ratio: float = 0.1, initial_permits: float = 10.0, max_permits: float = 10.0) -> None:
+		if ratio < 0 or initial_permits < 0 or max_permits <= 0:
+			msg = "ratio 和 initial_permits 不能为负数, max_permits 必须为正数"
+			raise ValueError(msg)
+		self._balance = min(initial_permits, max_permits)
+		self._lock = Lock()
+		self._max_permits = max_permits
+		self._ratio = ratio
+
+	@property
+	def available(self) -> float:
+		"""当前剩余许可"""
+		with self._lock:
+			return self._balance
+
+	def record_success(self) -> None:
+		"""记录一次成功调用, 按比例补充许可"""
+		with self._lock:
+			self._balance = min(self._balance + self._ratio, self._max_permits)
+
+	def try_withdraw(self) -> bool:
+		"""尝试消耗一个重试许可, 预算不足时返回 False"""
+		with self._lock:
+			if self._balance < 1:
+				return False
+			self._balance -= 1
+			return True
+
+
+class CallTimeoutError(TimeoutError):
+	"""调用未在 timeout 秒内完成; 与被调用函数自身抛出的异常 (包括 TimeoutError) 区分"""
+
+	def __init__(self, timeout: float) -> None:
+		super().__init__(f"调用超过 {timeout} 秒未完成")
+		self.timeout = timeout
+
+
+def call_with_timeout[T](func: Callable[..., T], timeout: float, *args: object, **kwargs: object) -> T:
+	"""
+	在后台线程中调用 func, timeout 秒内完成时返回结果或原样抛出其异常, 否则抛出 CallTimeoutError
+	Python 无法强制终止线程, 超时后 func 仍在守护线程中运行至结束, 结果被丢弃; 守护线程不会阻止程序退出
+	"""
+	future: Future[T] = Future()
+
+	def run() -> None:
+		try:
+			future.set_result(func(*args, **kwargs))
+		except BaseException as e:  # noqa: BLE001
+			future.set_exception(e)
+
+	Thread(target=run, daemon=True).start()
+	# 使用 wait 判断是否完成, 避免把 func 抛出的 TimeoutError 误认为超时
+	done, _ = wait([future], timeout=timeout)
+	if not done:
+		raise CallTimeoutError(timeout)
+	return future.result()
+
+
+def with_timeout(timeout: float) -> Callable[[Callable], Callable]:
+	"""限制被装饰函数单次调用耗时的装饰器, 超时抛出 CallTimeoutError, 函数自身的异常原样抛出"""
+
+	def decorator(func: Callable) -> Callable:
+		@wraps(func)
+		def wrapper(*args, **kwargs):  # noqa: ANN002, ANN003, ANN202
+			return call_with_timeout(func, timeout, *args, **kwargs)
+
+		return wrapper
+
+	return decorator
+
+
+def with_retry_and_timeout(
+	timeout: float,
+	delays: Iterable[float],
+	retry_if: Callable[[Exception], bool] | None = None,
+	sleep_func: Callable[[float], None] = sleep,
+) -> Callable[[Callable], Callable]:
+	"""
+	失败时重试且每次尝试单独限时的装饰器, delays 为每次重试前的等待秒数 (如 ClientConfig.retry_delays 的结果), 共尝试 len(delays) + 1 次
+	超时的尝试总是重试; 函数自身的异常由 retry_if 判断是否重试, 未设置时总是重试; 全部失败后抛出最后一次的异常
+	"""
+	waits = tuple(delays)
+
+	def decorator(func: Callable) -> Callable:
+		@wraps(func)
+		def wrapper(*args, **kwargs):  # noqa: ANN002, ANN003, ANN202
+			for delay in waits:
+				try:
+					return call_with_timeout(func, timeout, *args, **kwargs)
+				except CallTimeoutError:
+					pass
+				except Exception as e:
+					if retry_if is not None and not retry_if(e):
+						raise
+				sleep_func(delay)
+			return call_with_timeout(func, timeout, *args, **kwargs)
+
+		return wrapper
+
+	return decorator
+
+
+@dataclass
+class TimingRow:
+	count: int = 0
+	last: float = 0.0
+	max: float = 0.0
+	min: float = 0.0
+	name: str = ""
+	total: float = 0.0
+
+
+@singleton
+class TimingRegistry:
+	"""
+	进程级耗时统计, 按名称汇总调用次数、总耗时、最短、最长和最近一次耗时 (秒)
+	最多保留 max_rows 个名称, 超出时淘汰最久未记录的名称, 避免名称过多时内存持续增长
+	"""
+
+	def __init__(self, clock: Callable[[], float] = perf_counter, max_rows: int = 512) -> None:
+		self.clock = clock
+		self.max_rows = max_rows
+		self._rows: OrderedDict[str, TimingRow] = OrderedDict()
+		self._lock = Lock()
+
+	def record(self, name: str, elapsed: float) -> None:
+		"""记录一次耗时"""
+		with self._lock:
+			row = self._rows.get(name)
+			if row is None:
+				self._rows[name] = TimingRow(count=1, last=elapsed, max=elapsed, min=elapsed, name=name, total=elapsed)
+				while len(self._rows) > self.max_rows:
+					self._rows.popitem(last=False)
+				return
+			self._rows.move_to_end(name)
+			row.count += 1
+			row.last = elapsed
+			row.max = max(row.max, elapsed)
+			row.min = min(row.min, elapsed)
+			row.total += elapsed
+
+	@contextmanager
+	def measure(self, name: str) -> Generator[None]:
+		"""测量代码块耗时, 提前返回或抛出异常时同样记录"""
+		start = self.clock()
+		try:
+			yield
+		finally:
+			self.record(name, self.clock() - start)
+
+	def report(self) -> list[TimingRow]:
+		"""按名称排序返回统计结果的副本"""
+		with self._lock:
+			return [TimingRow(**vars(row)) for _, row in sorted(self._rows.items())]
+
+	def reset(self) -> None:
+		"""清空统计"""
+		with self._lock:
+			self._rows.clear()
+
+
+def timed(name: str) -> Callable[[Callable], Callable]:
+	"""记录被装饰函数每次调用耗时的装饰器"""
+
+	def decorator(func: Callable) -> Callable:
+		@wraps(func)
+		def wrapper(*args, **kwargs):  # noqa: ANN002, ANN003, ANN202
+			with TimingRegistry().measure(name):
+				return func(*args, **kwargs)
+
+		return wrapper
+
+	return decorator
+
+
+@dataclass
+class BatchReport:
+	cancelled: bool = False
+	failed: list[tuple[int, Exception]] = field(default_factory=list)
+	succeeded: int = 0
+
+
+class BatchRunner:
+	"""
+	分批处理任务: 每批内并发执行 (不超过 concurrency), 批次之间暂停 delay 秒
+	jitter 为暂停时间的随机抖动比例 (0~1), 实际暂停在 [delay * (1 - jitter), delay] 之间, 单个任务失败不会中止运行, 除非设置 fail_fast
+	"""
+
+	def __init__(
+		self,
+		batch_size: int,
+		delay: float,
+		jitter: float = 0.0,
+		concurrency: int = 1,
+		*,
+		fail_fast: bool = False,
+		sleep_func: Callable[[float], None] = sleep,
+		rng: Random | None = None,
+	) -> None:
+		if batch_size <= 0:
+			msg = "batch_size 必须为正整数"
+			raise ValueError(msg)
+		if not 0 <= jitter <= 1:
+			msg = "jitter 必须位于 [0, 1] 区间"
+			raise ValueError(msg)
+		self.batch_size = batch_size
+		self.delay = delay
+		self.jitter = jitter
+		self.fail_fast = fail_fast
+		self._limiter = ConcurrencyLimiter(concurrency)
+		self._sleep = sleep_func
+		self._rng = rng or Random()
+		self._cancel = Event()
+
+	def cancel(self) -> None:
+		"""请求停止, 当前批次完成后结束"""
+		self._cancel.set()
+
+	def run[T](self, items: Iterable[T], func: Callable[[T], object]) -> BatchReport:
+		"""处理全部元素并返回成功数与失败明细 (元素下标, 异常)"""
+		self._cancel.clear()
+		report = BatchReport()
+		item_list = list(items)
+		for start in range(0, len(item_list), self.batch_size):
+			if start and self.delay > 0:
+				self._sleep(self.delay * (1 - self.jitter * self._rng.random()))
+			if self._cancel.is_set():
+				report.cancelled = True
+				break
+			results = self._limiter.map_concurrent(item_list[start : start + self.batch_size], func)
+			for offset, result in enumerate(results):
+				if isinstance(result, Exception):
+					report.failed.append((start + offset, result))
+				else:
+					report.succeeded += 1
+			if report.failed and self.fail_fast:
+				break
+		return report
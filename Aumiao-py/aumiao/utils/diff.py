@@ -0,0 +1,210 @@
+from __future__ import annotations
+
+from copy import deepcopy
+from dataclasses import dataclass
+from typing import TYPE_CHECKING, Any, Literal, get_args
+
+if TYPE_CHECKING:
+	from collections.abc import Iterable
+
+# ========== 类型定义 ==========
+PatchOp = Literal["add", "remove", "replace", "move", "copy", "test"]
+# 需要携带 value 或 from 字段的操作
+VALUE_OPS: tuple[PatchOp, ...] = ("add", "replace", "test")
+FROM_OPS: tuple[PatchOp, ...] = ("move", "copy")
+
+
+class PatchError(ValueError):
+	"""补丁格式错误或无法应用到目标文档"""
+
+
+@dataclass
+class PatchOperation:
+	# JSON 中的字段名为 from, 与 Python 关键字冲突
+	from_path: str | None = None
+	op: PatchOp = "add"
+	path: str = ""
+	value: Any = None
+
+	def to_dict(self) -> dict[str, Any]:
+		"""转换为 RFC 6902 格式的字典, 只包含该操作需要的字段"""
+		result: dict[str, Any] = {"op": self.op, "path": self.path}
+		if self.op in VALUE_OPS:
+			result["value"] = self.value
+		if self.op in FROM_OPS:
+			result["from"] = self.from_path
+		return result
+
+	@classmethod
+	def from_dict(cls, data: dict[str, Any]) -> PatchOperation:
+		"""从 RFC 6902 格式的字典创建操作, 缺少必需字段时抛出 PatchError"""
+		op = data.get("op")
+		if op not in get_args(PatchOp):
+			msg = f"未知的补丁操作: {op!r}"
+			raise PatchError(msg)
+		if not isinstance(data.get("path"), str):
+			msg = f"{op} 操作缺少 path 字段"
+			raise PatchError(msg)
+		if op in VALUE_OPS and "value" not in data:
+			msg = f"{op} 操作缺少 value 字段"
+			raise PatchError(msg)
+		if op in FROM_OPS and not isinstance(data.get("from"), str):
+			msg = f"{op} 操作缺少 from 字段"
+			raise PatchError(msg)
+		return cls(from_path=data.get("from"), op=op, path=data["path"], value=data.get("value"))
+
+
+def escape_token(token: str) -> str:
+	"""转义 JSON Pointer 中的单个路径片段"""
+	return token.replace("~", "~0").replace("/", "~1")
+
+
+def parse_pointer(pointer: str) -> list[str]:
+	"""将 JSON Pointer 拆分为路径片段, 空字符串表示整个文档"""
+	if not pointer:
+		return []
+	if not pointer.startswith("/"):
+		msg = f"JSON Pointer 必须以 / 开头: {pointer!r}"
+		raise PatchError(msg)
+	return [token.replace("~1", "/").replace("~0", "~") for token in pointer[1:].split("/")]
+
+
+def json_equal(left: object, right: object) -> bool:
+	"""按 JSON 语义比较, 与 Python 不同, 布尔值与数字不相等"""
+	if isinstance(left, bool) or isinstance(right, bool):
+		return type(left) is type(right) and left == right
+	if isinstance(left, dict) and isinstance(right, dict):
+		return left.keys() == right.keys() and all(json_equal(left[key], right[key]) for key in left)
+	if isinstance(left, list) and isinstance(right, list):
+		return len(left) == len(right) and all(json_equal(a, b) for a, b in zip(left, right, strict=True))
+	if isinstance(left, (dict, list)) or isinstance(right, (dict, list)):
+		return False
+	return left == right
+
+
+def generate_patch(source: object, target: object, path: str = "") -> list[PatchOperation]:
+	"""
+	生成将 source 变为 target 的补丁, 只使用 add、remove、replace 操作
+	字典逐键比较; 列表逐项比较, 多出的元素从末尾删除或追加
+	"""
+	if isinstance(source, dict) and isinstance(target, dict):
+		operations = [PatchOperation(op="remove", path=f"{path}/{escape_token(key)}") for key in source if key not in target]
+		for key, value in target.items():
+			child = f"{path}/{escape_token(key)}"
+			if key in source:
+				operations.extend(generate_patch(source[key], value, child))
+			else:
+				operations.append(PatchOperation(op="add", path=child, value=deepcopy(value)))
+		return operations
+	if isinstance(source, list) and isinstance(target, list):
+		common = min(len(source), len(target))
+		operations = [operation for index in range(common) for operation in generate_patch(source[index], target[index], f"{path}/{index}")]
+		# 从末尾开始删除, 保证前面的下标不变
+		operations.extend(PatchOperation(op="remove", path=f"{path}/{index}") for index in range(len(source) - 1, common - 1, -1))
+		operations.extend(PatchOperation(op="add", path=f"{path}/{index}", value=deepcopy(target[index])) for index in range(common, len(target)))
+		return operations
+	if json_equal(source, target):
+		return []
+	return [PatchOperation(op="replace", path=path, value=deepcopy(target))]
+
+
+def _list_index(container: list, token: str, *, allow_end: bool) -> int:
+	"""解析列表下标, allow_end 为 True 时允许指向末尾之后 (用于插入)"""
+	if token == "-" and allow_end:
+		return len(container)
+	if not token.isdigit() or (len(token) > 1 and token.startswith("0")):
+		msg = f"无效的列表下标: {token!r}"
+		raise PatchError(msg)
+	index = int(token)
+	if index > len(container) or (index == len(container) and not allow_end):
+		msg = f"列表下标越界: {index}"
+		raise PatchError(msg)
+	return index
+
+
+def _get(doc: object, tokens: list[str]) -> Any:
+	current = doc
+	for token in tokens:
+		if isinstance(current, dict):
+			if token not in current:
+				msg = f"路径不存在: /{'/'.join(escape_token(item) for item in tokens)}"
+				raise PatchError(msg)
+			current = current[token]
+		elif isinstance(current, list):
+			current = current[_list_index(current, token, allow_end=False)]
+		else:
+			msg = f"路径不存在: /{'/'.join(escape_token(item) for item in tokens)}"
+			raise PatchError(msg)
+	return current
+
+
+def _add(doc: object, tokens: list[str], value: object) -> Any:
+	if not tokens:
+		return value
+	parent = _get(doc, tokens[:-1])
+	if isinstance(parent, dict):
+		parent[tokens[-1]] = value
+	elif isinstance(parent, list):
+		parent.insert(_list_index(parent, tokens[-1], allow_end=True), value)
+	else:
+		msg = f"无法在非容器值下添加: /{'/'.join(escape_token(item) for item in tokens)}"
+		raise PatchError(msg)
+	return doc
+
+
+def _remove(doc: object, tokens: list[str]) -> Any:
+	"""删除并返回指定位置的值"""
+	if not tokens:
+		msg = "不能删除整个文档"
+		raise PatchError(msg)
+	parent = _get(doc, tokens[:-1])
+	_get(doc, tokens)
+	if isinstance(parent, dict):
+		return parent.pop(tokens[-1])
+	return parent.pop(_list_index(parent, tokens[-1], allow_end=False))
+
+
+def _replace(doc: object, tokens: list[str], value: object) -> Any:
+	if not tokens:
+		return value
+	parent = _get(doc, tokens[:-1])
+	_get(doc, tokens)
+	if isinstance(parent, dict):
+		parent[tokens[-1]] = value
+	else:
+		parent[_list_index(parent, tokens[-1], allow_end=False)] = value
+	return doc
+
+
+def apply_patch(doc: object, patch: Iterable[PatchOperation]) -> Any:
+	"""
+	将补丁应用到文档的副本上并返回结果, 原文档不会被修改
+	任一操作失败 (包括 test 不通过) 时抛出 PatchError, 不会返回部分应用的结果
+	"""
+	result = deepcopy(doc)
+	for operation in patch:
+		tokens = parse_pointer(operation.path)
+		if operation.op == "add":
+			result = _add(result, tokens, deepcopy(operation.value))
+		elif operation.op == "remove":
+			_remove(result, tokens)
+		elif operation.op == "replace":
+			result = _replace(result, tokens, deepcopy(operation.value))
+		elif operation.op in FROM_OPS:
+			from_tokens = parse_pointer(operation.from_path or "")
+			if operation.op == "move":
+				if tokens[: len(from_tokens)] == from_tokens and tokens != from_tokens:
+					msg = f"不能将 {operation.from_path} 移动到其子路径 {operation.path}"
+					raise PatchError(msg)
+				value = _remove(result, from_tokens) if from_tokens else result
+			else:
+				value = deepcopy(_get(result, from_tokens))
+			result = _add(result, tokens, value)
+		elif operation.op == "test":
+			if not json_equal(_get(result, tokens), operation.value):
+				msg = f"test 操作失败: {operation.path} 的值与预期不符"
+				raise PatchError(msg)
+		else:
+			msg = f"未知的补丁操作: {operation.op!r}"
+			raise PatchError(msg)
+	return result
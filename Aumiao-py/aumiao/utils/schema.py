@@ -0,0 +1,156 @@
+from __future__ import annotations
+
+from dataclasses import MISSING, asdict, fields, is_dataclass
+from inspect import getsource
+from pathlib import Path
+from re import compile as re_compile
+from types import NoneType, UnionType
+from typing import TYPE_CHECKING, Any, Literal, Union, get_args, get_origin, get_type_hints
+
+from aumiao.utils.data import DEFAULT_SETTING_DATA, PASSWORD_LOGIN_METHODS, CodeMaoFile, CodeMaoSetting, CredentialStore, LogLevel
+from aumiao.utils.diff import json_equal
+
+if TYPE_CHECKING:
+	from collections.abc import Mapping
+
+DRAFT_07 = "http://json-schema.org/draft-07/schema#"
+# 字段定义行与被注释掉的字段, 用于从源码中提取字段上方的注释
+FIELD_LINE = re_compile(r"^([A-Za-z_]\w*)\s*:")
+# 类型注解无法表达的约束, 键为以 . 分隔的字段路径, 与 SettingValidator 的校验保持一致
+FIELD_CONSTRAINTS: dict[str, dict[str, Any]] = {
+	"PARAMETER.cache_ttl_minutes": {"minimum": 0},
+	"PARAMETER.credential_store": {"enum": list(get_args(CredentialStore))},
+	"PARAMETER.endpoint_log_overrides": {"additionalProperties": {"enum": list(get_args(LogLevel)), "type": "string"}},
+	"PARAMETER.log_level": {"enum": ["", *get_args(LogLevel)]},
+	"PARAMETER.max_data_file_bytes": {"minimum": 0},
+	"PARAMETER.password_login_method": {"enum": list(PASSWORD_LOGIN_METHODS)},
+	"PARAMETER.report_work_max": {"minimum": 0},
+	"PARAMETER.spam_del_max": {"minimum": 0},
+}
+JSON_TYPES: dict[type, str] = {bool: "boolean", float: "number", int: "integer", Path: "string", str: "string"}
+
+
+def field_comments(data_class: type) -> dict[str, str]:
+	"""读取数据类源码, 将紧挨在字段定义上方的注释作为字段说明; 无法获取源码时返回空字典"""
+	try:
+		lines = getsource(data_class).splitlines()
+	except (OSError, TypeError):
+		return {}
+	comments: dict[str, str] = {}
+	pending: list[str] = []
+	for raw_line in lines[1:]:
+		line = raw_line.strip()
+		if line.startswith("#"):
+			text = line.lstrip("#").strip()
+			# 被注释掉的字段定义不是说明
+			pending = [] if FIELD_LINE.match(text) else [*pending, text]
+			continue
+		match = FIELD_LINE.match(line)
+		if match and pending:
+			comments[match.group(1)] = ", ".join(pending)
+		pending = []
+	return comments
+
+
+def type_schema(field_type: Any, path: str = "") -> dict[str, Any]:
+	"""将类型注解转换为 JSON Schema 片段"""
+	origin = get_origin(field_type)
+	args = get_args(field_type)
+	if origin is Literal:
+		return {"enum": list(args), "type": JSON_TYPES.get(type(args[0]), "string")} if args else {}
+	if origin in {Union, UnionType}:
+		return {"anyOf": [{"type": "null"} if arg is NoneType else type_schema(arg, path) for arg in args]}
+	if origin is list:
+		return {"items": type_schema(args[0], f"{path}[]"), "type": "array"} if args else {"type": "array"}
+	if origin is dict:
+		return {"additionalProperties": type_schema(args[1], f"{path}.*"), "type": "object"} if args else {"type": "object"}
+	if isinstance(field_type, type) and is_dataclass(field_type):
+		return dataclass_schema(field_type, {}, path)
+	if field_type in JSON_TYPES:
+		return {"type": JSON_TYPES[field_type]}
+	# Any 等无法描述的类型不做限制
+	return {}
+
+
+def dataclass_schema(data_class: type, defaults: Mapping[str, Any], path: str = "") -> dict[str, Any]:
+	"""生成数据类对应的对象 Schema, defaults 中的值优先作为默认值, 其余使用字段自身的默认值"""
+	hints = get_type_hints(data_class)
+	comments = field_comments(data_class)
+	properties: dict[str, Any] = {}
+	for item in fields(data_class):
+		field_path = f"{path}.{item.name}" if path else item.name
+		field_type = hints[item.name]
+		nested_defaults = defaults.get(item.name, {})
+		if isinstance(field_type, type) and is_dataclass(field_type):
+			schema = dataclass_schema(field_type, nested_defaults if isinstance(nested_defaults, dict) else {}, field_path)
+		else:
+			schema = type_schema(field_type, field_path)
+			if item.name in defaults:
+				schema["default"] = defaults[item.name]
+			elif item.default is not MISSING:
+				schema["default"] = item.default
+			elif item.default_factory is not MISSING:
+				value = item.default_factory()
+				schema["default"] = asdict(value) if is_dataclass(value) else value
+		schema.update(FIELD_CONSTRAINTS.get(field_path, {}))
+		if item.name in comments:
+			schema["description"] = comments[item.name]
+		properties[item.name] = schema
+	# 与 find_unknown_keys 一致, 未知的键通常是拼写错误
+	return {"additionalProperties": False, "properties": properties, "type": "object"}
+
+
+def setting_schema() -> dict[str, Any]:
+	"""生成 setting.json 的 JSON Schema (draft-07), 默认值取自 DEFAULT_SETTING_DATA"""
+	return {"$schema": DRAFT_07, "title": "Aumiao setting.json", **dataclass_schema(CodeMaoSetting, DEFAULT_SETTING_DATA)}
+
+
+def write_schema(path: Path) -> None:
+	"""将 setting.json 的 Schema 写入指定文件, 供编辑器和前端校验使用"""
+	CodeMaoFile().file_write(path=path, content=setting_schema())
+
+
+def _type_matches(value: object, expected: str) -> bool:
+	# JSON 中布尔值不属于数字
+	checks = {
+		"array": lambda: isinstance(value, list),
+		"boolean": lambda: isinstance(value, bool),
+		"integer": lambda: isinstance(value, int) and not isinstance(value, bool),
+		"null": lambda: value is None,
+		"number": lambda: isinstance(value, (int, float)) and not isinstance(value, bool),
+		"object": lambda: isinstance(value, dict),
+		"string": lambda: isinstance(value, str),
+	}
+	return checks[expected]()
+
+
+def validate(instance: object, schema: Mapping[str, Any], path: str = "$") -> list[str]:
+	"""
+	按 Schema 校验 JSON 数据, 返回所有错误 (格式为 路径: 原因)
+	只支持本模块生成的 Schema 用到的关键字: type、enum、minimum、anyOf、properties、additionalProperties、items
+	"""
+	if "anyOf" in schema:
+		if any(not validate(instance, option, path) for option in schema["anyOf"]):
+			return []
+		return [f"{path}: 不符合任一可选类型"]
+	if "type" in schema and not _type_matches(instance, schema["type"]):
+		return [f"{path}: 类型应为 {schema['type']}, 实际为 {type(instance).__name__}"]
+	errors: list[str] = []
+	if "enum" in schema and not any(json_equal(instance, option) for option in schema["enum"]):
+		errors.append(f"{path}: 取值 {instance!r} 不在可选值 {schema['enum']} 中")
+	if "minimum" in schema and isinstance(instance, (int, float)) and instance < schema["minimum"]:
+		errors.append(f"{path}: 取值 {instance!r} 小于最小值 {schema['minimum']}")
+	if isinstance(instance, dict):
+		properties = schema.get("properties", {})
+		additional = schema.get("additionalProperties", True)
+		for key, value in instance.items():
+			if key in properties:
+				errors.extend(validate(value, properties[key], f"{path}.{key}"))
+			elif additional is False:
+				errors.append(f"{path}.{key}: 未知的配置项")
+			elif isinstance(additional, dict):
+				errors.extend(validate(value, additional, f"{path}.{key}"))
+	if isinstance(instance, list) and "items" in schema:
+		for index, item in enumerate(instance):
+			errors.extend(validate(item, schema["items"], f"{path}[{index}]"))
+	return errors
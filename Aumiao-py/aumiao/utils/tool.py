@@ -2,36 +2,71 @@ from __future__ import annotations
 
 from abc import ABC, abstractmethod
 from base64 import b64decode
-from collections.abc import Callable, Iterable, Mapping
+from collections.abc import Callable, Generator, Iterable, Mapping
 from dataclasses import asdict, dataclass, fields, is_dataclass
+from datetime import datetime, timedelta
 from functools import lru_cache
 from hashlib import sha256
 from html import unescape
-from json import loads
-from random import choice, randint, random
-from re import DOTALL, IGNORECASE, Match, findall, sub
-from time import localtime, strftime, time
+from itertools import zip_longest
+from json import dumps, loads
+from logging import getLogger
+from math import inf, nan
+from os import environ
+from random import Random
+from re import DOTALL, IGNORECASE, Match, Pattern, sub
+from re import compile as re_compile
+from statistics import fmean, pstdev
+from time import localtime, monotonic, sleep, strftime, time
 from types import GeneratorType
-from typing import Any, ClassVar, Final, Literal, TypeVar, cast
+from typing import TYPE_CHECKING, Any, ClassVar, Final, Literal, TypeVar, cast
+from unicodedata import category, east_asian_width
+from urllib.parse import quote, unquote
+from zoneinfo import ZoneInfo
 
 from cryptography.hazmat.primitives import hashes
 from cryptography.hazmat.primitives.ciphers.aead import AESGCM
 
 from aumiao.utils.decorator import singleton
 
+if TYPE_CHECKING:
+	from aumiao.utils.data import CommentTemplate
+
+logger = getLogger(__name__)
 # ========== 类型定义 ==========
 T = TypeVar("T")
 DataDict = dict[str, Any]
 DataObject = DataDict | list[DataDict] | Iterable[DataDict]
+# 时间边界: 整点、零点、周一零点, 或以秒为单位的自定义周期 (从 Unix 纪元起对齐)
+TimeBoundary = Literal["hour", "day", "week"] | float
 # ========== 常量定义 ==========
 FILE_SIZE: Final[int] = 1024
 CLASS_NUM_LIMIT: Final[int] = 12
+# CJK 统一表意文字及其扩展区、兼容表意文字的码位范围
+CJK_RANGES: Final[tuple[tuple[int, int], ...]] = (
+	(0x3400, 0x4DBF),
+	(0x4E00, 0x9FFF),
+	(0xF900, 0xFAFF),
+	(0x20000, 0x2A6DF),
+	(0x2A700, 0x2EBEF),
+	(0x30000, 0x3134F),
+)
 LETTER_PROBABILITY: Final[float] = 0.3
 SPECIALTY_PROBABILITY: Final[float] = 0.4
 NAME_SUFFIX_PROBABILITY: Final[float] = 0.2
 MIN_DATA_LENGTH: Final[int] = 13
 MIN_CHOICE_LENGTH: Final[int] = 2
 AES_IV_LENGTH: Final[int] = 12
+COOKIE_SAFE_CHARS: Final[str] = "!#$&'()*+-./:<>?@[]^_`{|}~"
+_MISSING: Final[object] = object()
+# ========== 预编译正则 ==========
+HTML_BLOCK_PATTERN: Final[Pattern[str]] = re_compile(r"<(?:div|p)\b [^>]*>(.*?)</(?:div|p)>", DOTALL | IGNORECASE)
+HTML_IMG_PATTERN: Final[Pattern[str]] = re_compile(r'<img\b [^>]*?src\s*=\s*("([^"]+)"|\'([^\']+)\'|([^\s>]+))[^>]*>', IGNORECASE)
+HTML_IMG_ALT_PATTERN: Final[Pattern[str]] = re_compile(r'\salt\s*=\s*("([^"]*)"|\'([^\']*)\'|([^\s>]+))', IGNORECASE)
+HTML_SPAN_PATTERN: Final[Pattern[str]] = re_compile(r"<span [^>]*>|</span>")
+HTML_TAG_PATTERN: Final[Pattern[str]] = re_compile(r"<[^>]+>")
+ENV_VAR_PATTERN: Final[Pattern[str]] = re_compile(r"\$\{([A-Za-z_][A-Za-z0-9_]*)\}")
+EMPTY_LINES_PATTERN: Final[Pattern[str]] = re_compile(r"\n {3,}")
 
 
 # ========== 颜色配置管理器 ==========
@@ -100,9 +135,9 @@ class NestedFilterStrategy(DataProcessorStrategy):
 	def __init__(self, path_cache: PathCache | None = None) -> None:
 		self._path_cache = path_cache or PathCache()
 
-	def process(self, data: tuple[DataObject, str, Iterable[object], bool]) -> list[DataDict]:
+	def process(self, data: tuple[DataObject, str, Iterable[object], bool, bool]) -> list[DataDict]:
 		"""过滤数据"""
-		data_object, id_path, target_values, strict_mode = data
+		data_object, id_path, target_values, strict_mode, match_missing_as_null = data
 		if not id_path or not isinstance(id_path, str):
 			msg = "id_path 必须是非空字符串"
 			raise ValueError(msg)
@@ -114,19 +149,33 @@ class NestedFilterStrategy(DataProcessorStrategy):
 		items = self._normalize_input(data_object)
 		if strict_mode:
 			return [item for item in items if self._get_nested_strict(item, path_keys) in target_set]
-		return [item for item in items if self._get_nested_safe(item, path_keys) in target_set]
+		if match_missing_as_null:
+			return [item for item in items if self._get_nested_safe(item, path_keys) in target_set]
+		# 缺失的路径不视为 None, 仅显式的 null 值才能匹配 None
+		return [item for item in items if (value := self._get_nested_or_missing(item, path_keys)) is not _MISSING and value in target_set]
 
 	@staticmethod
 	def _normalize_input(data: DataObject) -> ...:
-		"""优化输入标准化逻辑"""
+		"""优化输入标准化逻辑, 警告写入日志"""
+		items, warnings = NestedFilterStrategy._normalize_input_with_warnings(data)
+		for warning in warnings:
+			logger.warning("%s: %s", warning.code, warning.message)
+		return items
+
+	@staticmethod
+	def _normalize_input_with_warnings(data: DataObject) -> tuple[Iterable[DataDict], list[DataWarning]]:
+		"""标准化输入, 同时返回非致命的警告"""
 		key: str = "items"
 		if isinstance(data, dict):
 			data = cast("dict", data)
 			if key in data and hasattr(data[key], "__iter__"):
-				return list(data[key])
-			return [data]
+				items = list(data[key])
+				# 空的 items 容器常见于接口无返回数据, 单独报告以便与过滤后为空区分
+				warnings = [DataWarning(code="empty_items_envelope", message=f"输入数据的 {key} 列表为空, 键: {list(data)}")] if not items else []
+				return items, warnings
+			return [data], []
 		if isinstance(data, (list, GeneratorType)) or hasattr(data, "__iter__"):
-			return data
+			return data, []
 		msg = f"输入数据必须是字典或可迭代的字典集合, 实际类型: {type(data).__name__}"
 		raise TypeError(msg)
 
@@ -141,6 +190,16 @@ class NestedFilterStrategy(DataProcessorStrategy):
 			current = current[key]
 		return current
 
+	@staticmethod
+	def _get_nested_or_missing(data: Mapping[str, Any], path_keys: tuple[str, ...]) -> object:
+		"""获取嵌套值, 路径缺失时返回哨兵对象以区分显式的 None"""
+		current: object = data
+		for key in path_keys:
+			if not isinstance(current, Mapping) or key not in current:
+				return _MISSING
+			current = current[key]
+		return current
+
 	@staticmethod
 	def _get_nested_safe(data: Mapping[str, Any], path_keys: tuple[str, ...]) -> object | None:
 		"""安全模式下的嵌套值获取"""
@@ -191,6 +250,47 @@ class FieldFilterStrategy(DataProcessorStrategy):
 		"""通用可迭代类型过滤"""
 		return (FieldFilterStrategy._filter_dict(item, include, exclude) for item in data)
 
+	@staticmethod
+	def filter_recursive(data: object, include: set[str] | None, exclude: set[str] | None, max_depth: int | None, depth: int = 0, prefix: str = "") -> object:
+		"""
+		逐层过滤字段, 不含点号的字段名在每一层都匹配, 点号路径 (如 parent.child) 只匹配对应层级
+		命中包含字段后保留其完整子树, 子树内只再应用排除字段与层数限制
+		列表元素与列表本身视为同一层, 超过 max_depth 的层级保持原样
+		"""
+		if isinstance(data, list):
+			return [FieldFilterStrategy.filter_recursive(item, include, exclude, max_depth, depth, prefix) for item in data]
+		if not isinstance(data, dict) or (max_depth is not None and depth > max_depth):
+			return data
+		result = {}
+		for key, value in data.items():
+			path = f"{prefix}{key}"
+			child_include = include
+			if include is not None:
+				if key in include or path in include:
+					child_include = None
+				elif not any(name.startswith(f"{path}.") for name in include):
+					continue
+			if exclude is not None and (key in exclude or path in exclude):
+				continue
+			result[key] = FieldFilterStrategy.filter_recursive(value, child_include, exclude, max_depth, depth + 1, f"{path}.")
+		return result
+
+
+@dataclass
+class DataWarning:
+	"""处理数据时产生的非致命警告"""
+
+	code: str
+	message: str
+
+
+@dataclass
+class CountByResult:
+	"""按字段计数结果"""
+
+	counts: dict[str, int]
+	total: int
+
 
 # ========== 数据处理器工厂 ==========
 @singleton
@@ -214,10 +314,43 @@ class DataProcessor:
 		target_values: Iterable[object],
 		*,
 		strict_mode: bool = False,
+		match_missing_as_null: bool = False,
 	) -> list[DataDict]:
-		"""过滤数据"""
+		"""过滤数据, match_missing_as_null 为 False 时缺失路径不会匹配 None"""
 		processor = DataProcessor.create_filter_processor()
-		return processor.process((data, id_path, target_values, strict_mode))
+		return processor.process((data, id_path, target_values, strict_mode, match_missing_as_null))
+
+	@staticmethod
+	def filter_by_nested_values_with_warnings(
+		data: DataObject,
+		id_path: str,
+		target_values: Iterable[object],
+		*,
+		strict_mode: bool = False,
+		match_missing_as_null: bool = False,
+	) -> tuple[list[DataDict], list[DataWarning]]:
+		"""同 filter_by_nested_values, 额外返回警告 (例如空的 items 容器), 便于区分接口无数据与过滤后为空"""
+		items, warnings = NestedFilterStrategy._normalize_input_with_warnings(data)  # noqa: SLF001
+		result = DataProcessor.filter_by_nested_values(list(items), id_path, target_values, strict_mode=strict_mode, match_missing_as_null=match_missing_as_null)
+		return result, warnings
+
+	@staticmethod
+	def filter_by_nested_predicate(
+		data: DataObject,
+		id_path: str,
+		predicate: Callable[[object], bool],
+		*,
+		strict_mode: bool = False,
+	) -> list[DataDict]:
+		"""按 id_path 处的值是否满足 predicate 过滤, 路径缺失的元素直接跳过 (严格模式下抛出异常)"""
+		if not id_path or not isinstance(id_path, str):
+			msg = "id_path 必须是非空字符串"
+			raise ValueError(msg)
+		path_keys = PathCache().get(id_path)
+		items = NestedFilterStrategy._normalize_input(data)  # noqa: SLF001
+		if strict_mode:
+			return [item for item in items if predicate(NestedFilterStrategy._get_nested_strict(item, path_keys))]  # noqa: SLF001
+		return [item for item in items if (value := NestedFilterStrategy._get_nested_or_missing(item, path_keys)) is not _MISSING and predicate(value)]  # noqa: SLF001
 
 	@staticmethod
 	def filter_fields(
@@ -235,6 +368,24 @@ class DataProcessor:
 		processor = DataProcessor.create_field_filter_processor()
 		return processor.process((data, include_set, exclude_set))
 
+	@staticmethod
+	def filter_fields_recursive(
+		data: DataObject,
+		*,
+		include: list[str] | None = None,
+		exclude: list[str] | None = None,
+		max_depth: int | None = None,
+	) -> DataObject:
+		"""递归过滤各层字段, max_depth 为 0 时等同于 filter_fields, 为 None 时不限层数"""
+		if include is not None and exclude is not None:
+			msg = "不能同时指定包含和排除字段"
+			raise ValueError(msg)
+		include_set = set(include) if include else None
+		exclude_set = set(exclude) if exclude else None
+		if not isinstance(data, dict | list):
+			data = list(data)
+		return cast("DataObject", FieldFilterStrategy.filter_recursive(data, include_set, exclude_set, max_depth))
+
 	@staticmethod
 	def get_nested_value(data: Mapping[str, Any], path: str, *, strict: bool = False) -> object | None:
 		"""获取嵌套值"""
@@ -244,6 +395,97 @@ class DataProcessor:
 			return NestedFilterStrategy._get_nested_strict(data, path_keys)  # noqa: SLF001
 		return NestedFilterStrategy._get_nested_safe(data, path_keys)  # noqa: SLF001
 
+	@staticmethod
+	def project(data: DataObject, projection: Mapping[str, str]) -> DataDict | list[DataDict]:
+		"""
+		按 {源字段: 新字段名} 选取并重命名字段, 未列出的字段被丢弃
+		源字段支持点号路径, 嵌套值会被提取到顶层; 缺失的字段不出现在结果中
+		"""
+		path_cache = PathCache()
+		paths = [(path_cache.get(source), target) for source, target in projection.items()]
+
+		def project_item(item: DataDict) -> DataDict:
+			result: DataDict = {}
+			for path_keys, target in paths:
+				value = NestedFilterStrategy._get_nested_or_missing(item, path_keys)  # noqa: SLF001
+				if value is not _MISSING:
+					result[target] = value
+			return result
+
+		if isinstance(data, Mapping):
+			return project_item(cast("DataDict", data))
+		return [project_item(item) for item in data]
+
+	@staticmethod
+	def zip_records(left: list[DataDict], right: list[DataDict], left_prefix: str, right_prefix: str) -> list[DataDict]:
+		"""逐项合并两个等长列表, 两侧的键分别加上前缀以避免冲突; 长度不一致时抛出 ValueError"""
+		if len(left) != len(right):
+			msg = f"长度不一致: {len(left)} vs {len(right)}"
+			raise ValueError(msg)
+		return DataProcessor.zip_longest_records(left, right, left_prefix, right_prefix)
+
+	@staticmethod
+	def zip_longest_records(left: list[DataDict], right: list[DataDict], left_prefix: str, right_prefix: str) -> list[DataDict]:
+		"""逐项合并两个列表, 较短一侧缺少的元素视为空对象"""
+		return [
+			{**{f"{left_prefix}{key}": value for key, value in left_item.items()}, **{f"{right_prefix}{key}": value for key, value in right_item.items()}}
+			for left_item, right_item in zip_longest(left, right, fillvalue={})
+		]
+
+	@staticmethod
+	def apply_defaults(data: DataObject, defaults: Mapping[str, Any]) -> DataDict | list[DataDict]:
+		"""用 defaults 浅层补全缺失的键, data 中已有的值优先; 列表输入逐项处理"""
+		if isinstance(data, Mapping):
+			return {**data, **{key: value for key, value in defaults.items() if key not in data}}
+		return [{**item, **{key: value for key, value in defaults.items() if key not in item}} for item in data]
+
+	@staticmethod
+	def deep_merge(base: Mapping[str, Any], override: Mapping[str, Any]) -> DataDict:
+		"""将 override 递归合并到 base 上并返回新字典: 两边都是字典时逐键合并, 其他值 (包括列表) 由 override 整体替换, 不修改输入"""
+		result = dict(base)
+		for key, value in override.items():
+			current = result.get(key)
+			result[key] = DataProcessor.deep_merge(current, value) if isinstance(current, Mapping) and isinstance(value, Mapping) else value
+		return result
+
+	@staticmethod
+	def chunked(iterable: Iterable[T], size: int) -> Generator[list[T]]:
+		"""将任意可迭代对象 (包括分页生成器) 按 size 分块, 最后不足一块的部分同样输出"""
+		if size <= 0:
+			msg = f"分块大小必须为正整数, 实际为 {size}"
+			raise ValueError(msg)
+		chunk: list[T] = []
+		for item in iterable:
+			chunk.append(item)
+			if len(chunk) == size:
+				yield chunk
+				chunk = []
+		if chunk:
+			yield chunk
+
+	@staticmethod
+	def count_by(data: DataObject, key_path: str, *, sort: bool = False) -> CountByResult:
+		"""
+		统计 key_path 处各取值的出现次数, 非字符串值按 JSON 序列化, 路径缺失的记入 __missing__
+		默认按首次出现的顺序排列, sort 为 True 时按次数从多到少排列 (次数相同按取值排序)
+		"""
+		path_keys = PathCache().get(key_path)
+		counts: dict[str, int] = {}
+		total = 0
+		for item in NestedFilterStrategy._normalize_input(data):  # noqa: SLF001
+			value = NestedFilterStrategy._get_nested_or_missing(item, path_keys)  # noqa: SLF001
+			if value is _MISSING:
+				bucket = "__missing__"
+			elif isinstance(value, str):
+				bucket = value
+			else:
+				bucket = dumps(value, ensure_ascii=False, default=str)
+			counts[bucket] = counts.get(bucket, 0) + 1
+			total += 1
+		if sort:
+			counts = dict(sorted(counts.items(), key=lambda pair: (-pair[1], pair[0])))
+		return CountByResult(counts=counts, total=total)
+
 	@staticmethod
 	def deduplicate(sequence: Iterable[str | int]) -> list[str]:
 		"""保持顺序去重"""
@@ -255,6 +497,23 @@ class DataProcessor:
 				result.append(item)
 		return result
 
+	@staticmethod
+	def deduplicate_by_key[K](sequence: Iterable[T], key_fn: Callable[[T], K]) -> list[T]:
+		"""按键函数保持顺序去重, 适用于字典等不可哈希元素"""
+		seen: set[K] = set()
+		result: list[T] = []
+		for item in sequence:
+			key = key_fn(item)
+			if key not in seen:
+				seen.add(key)
+				result.append(item)
+		return result
+
+	@staticmethod
+	def deduplicate_values(sequence: Iterable[object]) -> list[object]:
+		"""保持顺序去重 JSON 值, 以序列化结果作为比较键"""
+		return DataProcessor.deduplicate_by_key(sequence, lambda item: dumps(item, sort_keys=True, ensure_ascii=False, default=str))
+
 
 # ========== 数据转换器 ==========
 @singleton
@@ -263,8 +522,20 @@ class DataConverter:
 
 	@staticmethod
 	def convert_cookie(cookie: dict[str, str]) -> str:
-		"""将字典格式 cookie 转换为字符串"""
-		return ";".join(f"{k}={v}" for k, v in cookie.items())
+		"""将字典格式 cookie 转换为字符串, 值部分进行百分号编码"""
+		return ";".join(f"{k}={quote(str(v), safe=COOKIE_SAFE_CHARS)}" for k, v in cookie.items())
+
+	@staticmethod
+	def parse_cookie_string(cookie_str: str) -> dict[str, str]:
+		"""将 cookie 字符串解析为字典, 值部分进行百分号解码"""
+		result: dict[str, str] = {}
+		for pair in cookie_str.split(";"):
+			if "=" not in pair:
+				continue
+			key, value = pair.split("=", 1)
+			if key.strip():
+				result[key.strip()] = unquote(value.strip())
+		return result
 
 	@staticmethod
 	def to_serializable(data: object) -> dict[str, object]:
@@ -283,6 +554,15 @@ class DataConverter:
 		msg = f"不支持的类型: {type(data).__name__}。支持类型: dict, 数据类实例, 或包含__dict__属性的对象"
 		raise TypeError(msg)
 
+	@staticmethod
+	def template_render(template: CommentTemplate | str, variables: Mapping[str, Any]) -> str:
+		"""用 variables 替换模板中的 {name} 占位符, 占位符缺失或格式错误时原样返回模板文本"""
+		text = template if isinstance(template, str) else template.text
+		try:
+			return text.format(**variables)
+		except (KeyError, ValueError, IndexError):
+			return text
+
 	@staticmethod
 	def bbcode_to_html(bbcode: str) -> str:
 		"""将 BBCode 转换为 HTML"""
@@ -381,39 +661,39 @@ class DataConverter:
 		html_content: str,
 		*,
 		replace_images: bool = True,
-		img_format: str = "[图片链接: {src}]",
+		img_format: str = "[图片: {src}]",
 		merge_empty_lines: bool = True,
 		unescape_entities: bool = True,
 		keep_line_breaks: bool = True,
 	) -> str:
-		"""将 HTML 转换为可配置的纯文本"""
+		"""
+		将 HTML 转换为可配置的纯文本
+		img_format 为图片替换模板, 支持 {src} (图片地址) 和 {alt} (alt 属性, 缺省为空字符串) 占位符
+		"""
 
 		def replace_img(match: Match) -> str:
 			src = next((g for g in match.groups()[1:] if g), "")
-			return img_format.format(src=unescape(src)) if src else img_format.format(src="")
+			alt_match = HTML_IMG_ALT_PATTERN.search(match.group(0))
+			alt = next((g for g in alt_match.groups()[1:] if g), "") if alt_match else ""
+			return img_format.format(src=unescape(src), alt=unescape(alt))
 
 		# 处理段落和 div 块
-		blocks = findall(r"<(?:div|p)\b [^>]*>(.*?)</(?:div|p)>", html_content, flags=DOTALL | IGNORECASE)
+		blocks = HTML_BLOCK_PATTERN.findall(html_content)
 		if not blocks:
 			blocks = [html_content]
 		processed = []
 		for block in blocks:
 			# 图片处理
 			if replace_images:
-				block = sub(  # noqa: PLW2901
-					r'<img\b [^>]*?src\s*=\s*("([^"]+)"|\'([^\']+)\'|([^\s>]+))[^>]*>',
-					replace_img,
-					block,
-					flags=IGNORECASE,
-				)
+				block = HTML_IMG_PATTERN.sub(replace_img, block)  # noqa: PLW2901
 			# 移除 span 标签但保留内容
-			block = sub(r"<span [^>]*>|</span>", "", block)  # noqa: PLW2901
+			block = HTML_SPAN_PATTERN.sub("", block)  # noqa: PLW2901
 			# 转换 HTML 实体
 			if unescape_entities:
 				block = unescape(block)  # noqa: PLW2901
 				block = block.replace("&nbsp;", " ")  # noqa: PLW2901
 			# 移除其他 HTML 标签但保留内容
-			text = sub(r"<[^>]+>", "", block)
+			text = HTML_TAG_PATTERN.sub("", block)
 			# 处理换行
 			if not keep_line_breaks:
 				text = text.replace("\n", " ")
@@ -421,9 +701,71 @@ class DataConverter:
 		# 构建结果
 		result = "\n\n".join(processed)
 		if merge_empty_lines:
-			result = sub(r"\n {3,}", "\n\n", result)
+			result = EMPTY_LINES_PATTERN.sub("\n\n", result)
 		return result.strip()
 
+	@staticmethod
+	def json_to_table(
+		data: list[DataDict],
+		columns: list[str] | None = None,
+		style: Literal["ascii", "unicode", "markdown"] = "ascii",
+		max_cell_width: int = 50,
+	) -> str:
+		"""
+		将字典列表渲染为表格文本, 未指定列时使用首行的全部键 (排序后)
+		宽度按显示宽度计算 (中日韩全角字符占两列), 单元格两侧各留两个空格, 超过 max_cell_width 列的内容截断并以 ... 结尾
+		"""
+		if not data:
+			return ""
+		columns = columns or sorted(data[0])
+
+		padding = 2
+
+		def char_width(char: str) -> int:
+			return 2 if east_asian_width(char) in {"F", "W"} else 1
+
+		def display_width(text: str) -> int:
+			return sum(char_width(char) for char in text)
+
+		def cell(value: object) -> str:
+			text = value if isinstance(value, str) else dumps(value, ensure_ascii=False, default=str)
+			text = text.replace("\n", " ")
+			if display_width(text) <= max_cell_width:
+				return text
+			kept, used = [], 0
+			for char in text:
+				if used + char_width(char) > max_cell_width - 3:
+					break
+				kept.append(char)
+				used += char_width(char)
+			return "".join(kept) + "..."
+
+		rows = [[cell(row.get(column, "")) for column in columns] for row in data]
+		widths = [max(display_width(text) for text in [column, *(row[index] for row in rows)]) for index, column in enumerate(columns)]
+
+		def render_row(cells: list[str], sep: str) -> str:
+			pad = " " * padding
+			return sep + sep.join(f"{pad}{text}{' ' * (width - display_width(text))}{pad}" for text, width in zip(cells, widths, strict=True)) + sep
+
+		if style == "markdown":
+			lines = [render_row(columns, "|"), "|" + "|".join("-" * (width + 2 * padding) for width in widths) + "|"]
+			lines.extend(render_row(row, "|") for row in rows)
+			return "\n".join(lines)
+		if style == "unicode":
+			horizontal, vertical = "─", "│"
+			top, middle, bottom = ("┌", "┬", "┐"), ("├", "┼", "┤"), ("└", "┴", "┘")
+		else:
+			horizontal, vertical = "-", "|"
+			top = middle = bottom = ("+", "+", "+")
+
+		def border(left: str, junction: str, right: str) -> str:
+			return left + junction.join(horizontal * (width + 2 * padding) for width in widths) + right
+
+		lines = [border(*top), render_row(columns, vertical), border(*middle)]
+		lines.extend(render_row(row, vertical) for row in rows)
+		lines.append(border(*bottom))
+		return "\n".join(lines)
+
 	@staticmethod
 	def bytes_to_human(size: int) -> str:
 		"""将字节数转换为易读格式"""
@@ -436,6 +778,29 @@ class DataConverter:
 
 
 # ========== 字符串处理器 ==========
+@dataclass
+class SubstringMatch:
+	"""
+	find_substrings 的匹配结果, 对应候选字符串 "major.minor" 的两段整数
+	例如评论标识 "123.456" 中 major 为评论 ID, minor 为回复 ID; 未匹配或无该段时为 None
+	"""
+
+	major: int | None = None
+	minor: int | None = None
+
+
+@dataclass
+class CharCategoryCount:
+	"""按类别统计的字符数, 各类别互不重叠"""
+
+	ascii: int = 0
+	cjk: int = 0
+	other: int = 0
+	punctuation: int = 0
+	total: int = 0
+	whitespace: int = 0
+
+
 @singleton
 class StringProcessor:
 	"""字符串处理工具类"""
@@ -446,8 +811,48 @@ class StringProcessor:
 		return "\u200b".join(text)
 
 	@staticmethod
-	def find_substrings(text: str, candidates: Iterable[str]) -> tuple[int | None, int | None]:
-		"""在候选中查找子字符串位置"""
+	def count_chars_by_category(text: str) -> CharCategoryCount:
+		"""统计空白、标点 (含中文标点)、CJK 表意文字、其余 ASCII 字符及其他字符的数量"""
+		result = CharCategoryCount(total=len(text))
+		for char in text:
+			code = ord(char)
+			if char.isspace():
+				result.whitespace += 1
+			elif category(char).startswith("P"):
+				result.punctuation += 1
+			elif any(start <= code <= end for start, end in CJK_RANGES):
+				result.cjk += 1
+			elif char.isascii():
+				result.ascii += 1
+			else:
+				result.other += 1
+		return result
+
+	@staticmethod
+	def cjk_ratio(text: str) -> float:
+		"""CJK 表意文字占全部字符的比例, 空字符串返回 0"""
+		counts = StringProcessor.count_chars_by_category(text)
+		return counts.cjk / counts.total if counts.total else 0.0
+
+	@staticmethod
+	def interpolate_env_vars(text: str) -> str:
+		"""将文本中的 ${VAR} 替换为对应环境变量的值, 变量未设置时抛出 ValueError"""
+
+		def replace_var(match: Match) -> str:
+			name = match.group(1)
+			if name not in environ:
+				msg = f"未定义的环境变量 {name}"
+				raise ValueError(msg)
+			return environ[name]
+
+		return ENV_VAR_PATTERN.sub(replace_var, text)
+
+	@staticmethod
+	def find_substrings(text: str, candidates: Iterable[str]) -> SubstringMatch:
+		"""
+		返回第一个包含 text 的候选, 并将其按 "major.minor" 解析为整数
+		无法解析为整数的候选会被跳过, 没有匹配时两段均为 None
+		"""
 		text_str = str(text)
 		for candidate in candidates:
 			if text_str in candidate:
@@ -458,8 +863,8 @@ class StringProcessor:
 				except ValueError:
 					continue
 				else:
-					return main_id, sub_id
-		return None, None
+					return SubstringMatch(major=main_id, minor=sub_id)
+		return SubstringMatch()
 
 
 # ========== 时间工具 ==========
@@ -481,8 +886,88 @@ class TimeUtils:
 		"""格式化时间戳为字符串"""
 		return strftime("%Y-%m-%d %H:%M:%S", localtime(ts))
 
+	@staticmethod
+	def seconds_until_next_boundary(boundary: TimeBoundary, now: datetime | None = None, tz: str | None = None) -> float:
+		"""
+		计算距离下一个时间边界的秒数, 缺省按本地时间, tz 可指定 IANA 时区名 (如 Asia/Shanghai)
+		零点与周一零点按墙上时间推算后再换算为时间戳, 夏令时切换当天的时长随之变化
+		"""
+		if tz is not None:
+			zone = ZoneInfo(tz)
+			current = now.astimezone(zone) if now else datetime.now(zone)
+		else:
+			# 不带时区的本地时间换算时间戳时使用系统时区规则, 可正确处理夏令时
+			current = now or datetime.now()
+		if boundary == "hour":
+			# 整点按绝对时间推进一小时, 夏令时回拨时重复的一小时也会计入
+			return current.replace(minute=0, second=0, microsecond=0).timestamp() + 3600 - current.timestamp()
+		if boundary == "day":
+			target = current.replace(hour=0, minute=0, second=0, microsecond=0) + timedelta(days=1)
+		elif boundary == "week":
+			target = current.replace(hour=0, minute=0, second=0, microsecond=0) + timedelta(days=7 - current.weekday())
+		elif isinstance(boundary, int | float) and boundary > 0:
+			return boundary - current.timestamp() % boundary
+		else:
+			msg = f"无效的时间边界: {boundary!r}"
+			raise ValueError(msg)
+		# 同一时区对象的带时区时间直接相减会忽略偏移变化, 因此换算为时间戳后再相减
+		return target.timestamp() - current.timestamp()
+
+	@staticmethod
+	def seconds_until_next_hour() -> int:
+		"""距离下一个整点的秒数"""
+		return int(TimeUtils.seconds_until_next_boundary("hour"))
+
+	@staticmethod
+	def seconds_until_midnight() -> int:
+		"""距离下一个零点的秒数"""
+		return int(TimeUtils.seconds_until_next_boundary("day"))
+
+	@staticmethod
+	def sleep_until_next_boundary(boundary: TimeBoundary) -> None:
+		"""阻塞至下一个时间边界, 以单调时钟的截止时间为准, 避免多次休眠累积误差"""
+		deadline = monotonic() + TimeUtils.seconds_until_next_boundary(boundary)
+		while (remaining := deadline - monotonic()) > 0:
+			sleep(remaining)
+
 
 # ========== 数据分析器 ==========
+@dataclass
+class MetricSpec:
+	"""
+	对比指标的显示配置
+	format 可选 count (千分位计数)、percentage (相对初始值的变化百分比)、duration (按秒显示为时长), 或自定义格式化函数
+	"""
+
+	field: str
+	label: str
+	unit: str = ""
+	format: Literal["count", "percentage", "duration"] | Callable[[int], str] = "count"
+
+	def format_diff(self, diff: int, before: int) -> str:
+		"""格式化变化量"""
+		if callable(self.format):
+			text = self.format(diff)
+		elif self.format == "percentage":
+			# 初始值为 0 时无法计算百分比, 退化为显示原始变化量
+			text = f"{diff / before * 100:+.1f}%" if before else f"{diff:+,}"
+		elif self.format == "duration":
+			sign = "-" if diff < 0 else "+"
+			hours, remainder = divmod(abs(diff), 3600)
+			minutes, seconds = divmod(remainder, 60)
+			text = sign + "".join(f"{value}{unit}" for value, unit in ((hours, "h"), (minutes, "m"), (seconds, "s")) if value) if diff else "+0s"
+		else:
+			text = f"{diff:+,}"
+		return f"{text} {self.unit}" if self.unit else text
+
+
+@dataclass
+class AnomalyResult:
+	expected_range: tuple[float, float]
+	is_anomaly: bool
+	score: float
+
+
 @singleton
 class DataAnalyzer:
 	"""数据分析工具类"""
@@ -491,20 +976,68 @@ class DataAnalyzer:
 		self,
 		before: dict | object,
 		after: dict | object,
-		metrics: dict[str, str],
+		metrics: dict[str, str] | list[MetricSpec],
 		timestamp_field: str | None = None,
 	) -> None:
-		"""对比数据集差异"""
+		"""对比数据集差异, metrics 可为 字段->标签 映射或 MetricSpec 列表"""
 		before_dict = self._to_dict(before)
 		after_dict = self._to_dict(after)
 		if timestamp_field:
 			fmt = TimeUtils.format_timestamp
 			print(f"时间段: {fmt(before_dict[timestamp_field])} → {fmt(after_dict[timestamp_field])}")
-		for field, label in metrics.items():
-			before_val = before_dict.get(field, 0)
-			after_val = after_dict.get(field, 0)
+		specs = [MetricSpec(field=field, label=label) for field, label in metrics.items()] if isinstance(metrics, dict) else metrics
+		for spec in specs:
+			before_val = before_dict.get(spec.field, 0)
+			after_val = after_dict.get(spec.field, 0)
 			diff = after_val - before_val
-			print(f"{label}: {diff:+} (当前: {after_val}, 初始: {before_val})")
+			print(f"{spec.label}: {spec.format_diff(diff, before_val)} (当前: {after_val}, 初始: {before_val})")
+
+	@staticmethod
+	def moving_average(data: list[int] | list[float], window: int) -> list[float]:
+		"""简单移动平均, 结果与输入等长, 前 window - 1 项因数据不足为 nan"""
+		if window <= 0:
+			msg = f"窗口大小必须为正整数, 实际为 {window}"
+			raise ValueError(msg)
+		result: list[float] = []
+		window_sum = 0.0
+		for index, value in enumerate(data):
+			window_sum += value
+			if index >= window:
+				window_sum -= data[index - window]
+			result.append(window_sum / window if index >= window - 1 else nan)
+		return result
+
+	@staticmethod
+	def exponential_moving_average(data: list[int] | list[float], alpha: float) -> list[float]:
+		"""指数移动平均, alpha 为平滑系数, 取值范围 (0, 1]"""
+		if not 0 < alpha <= 1:
+			msg = f"平滑系数必须位于 (0, 1] 区间, 实际为 {alpha}"
+			raise ValueError(msg)
+		result: list[float] = []
+		for value in data:
+			result.append(float(value) if not result else alpha * value + (1 - alpha) * result[-1])
+		return result
+
+	def anomaly_score(self, history: list, latest: dict | object, field: str, z_threshold: float = 3.0) -> AnomalyResult:
+		"""
+		判断最新快照相对上一快照的变化是否异常
+		以历史快照间逐次变化量的均值和标准差计算 Z 分数, |Z| > z_threshold 视为异常
+		"""
+		if len(history) < 2:  # noqa: PLR2004
+			msg = "至少需要两个历史快照才能计算变化量"
+			raise ValueError(msg)
+		values = [self._to_dict(snapshot).get(field, 0) for snapshot in history]
+		changes = [current - previous for previous, current in zip(values, values[1:], strict=False)]
+		latest_change = self._to_dict(latest).get(field, 0) - values[-1]
+		mean = fmean(changes)
+		std = pstdev(changes, mean)
+		expected_range = (mean - z_threshold * std, mean + z_threshold * std)
+		if std == 0:
+			# 历史变化恒定时, 任何偏离都视为异常
+			score = 0.0 if latest_change == mean else inf if latest_change > mean else -inf
+		else:
+			score = (latest_change - mean) / std
+		return AnomalyResult(expected_range=expected_range, is_anomaly=abs(score) > z_threshold, score=score)
 
 	@staticmethod
 	def _to_dict(data: dict | object) -> dict:
@@ -535,20 +1068,22 @@ class EduDataGenerator:
 		*,
 		use_letters: bool = False,
 		add_specialty: bool = False,
+		seed: int | None = None,
 	) -> list[str]:
-		"""生成随机班级名称"""
+		"""生成随机班级名称, 指定 seed 时结果可复现"""
+		rng = Random(seed)
 
 		def number_to_chinese(n: int) -> str:
 			return cls._CHINESE_NUMBERS[n - 1] if 1 <= n <= CLASS_NUM_LIMIT else str(n)
 
 		class_names = []
 		for _ in range(num_classes):
-			grade = randint(grade_range[0], grade_range[1])
+			grade = rng.randint(grade_range[0], grade_range[1])
 			grade_str = f"{number_to_chinese(grade)} 年级"
-			class_num = choice(["A", "B", "C", "D"]) if use_letters and random() < LETTER_PROBABILITY else str(randint(1, 20))
+			class_num = rng.choice(["A", "B", "C", "D"]) if use_letters and rng.random() < LETTER_PROBABILITY else str(rng.randint(1, 20))
 			specialty = ""
-			if add_specialty and random() < SPECIALTY_PROBABILITY:
-				specialty = choice(cls._SPECIALTIES)
+			if add_specialty and rng.random() < SPECIALTY_PROBABILITY:
+				specialty = rng.choice(cls._SPECIALTIES)
 			class_name = f"{grade_str}{class_num}{specialty} 班"
 			class_names.append(class_name)
 		return class_names
@@ -558,18 +1093,21 @@ class EduDataGenerator:
 		cls,
 		num_students: int,
 		gender: Literal["male", "female"] | None = None,
+		*,
+		seed: int | None = None,
 	) -> list[str]:
-		"""生成随机学生姓名"""
+		"""生成随机学生姓名, 指定 seed 时结果可复现"""
+		rng = Random(seed)
 		names = []
 		for _ in range(num_students):
-			surname = choice(cls._SURNAMES)
-			current_gender = gender or choice(["male", "female"])
-			first_name = choice(cls._MALE_NAMES) if current_gender == "male" else choice(cls._FEMALE_NAMES)
+			surname = rng.choice(cls._SURNAMES)
+			current_gender = gender or rng.choice(["male", "female"])
+			first_name = rng.choice(cls._MALE_NAMES) if current_gender == "male" else rng.choice(cls._FEMALE_NAMES)
 			# 添加后缀
-			if random() < NAME_SUFFIX_PROBABILITY:
-				suffix = choice(["儿", "然", "轩", "瑶", "豪", "菲"])
+			if rng.random() < NAME_SUFFIX_PROBABILITY:
+				suffix = rng.choice(["儿", "然", "轩", "瑶", "豪", "菲"])
 				if current_gender == "male" and suffix in {"儿", "瑶", "菲"}:
-					suffix = choice(["然", "轩", "豪"])
+					suffix = rng.choice(["然", "轩", "豪"])
 				first_name += suffix
 			names.append(f"{surname}{first_name}")
 		return names
@@ -1,8 +1,10 @@
+from argparse import ArgumentParser, Namespace
 from collections.abc import Callable
 from dataclasses import dataclass
 from functools import partial, wraps
 from pathlib import Path
 from platform import system
+from sys import argv
 from sys import exit as exits
 from typing import Any, Literal, TypeVar, cast
 
@@ -11,6 +13,7 @@ from aumiao.core.compiler import decompile_work
 from aumiao.core.deepser import CodeMaoTool
 from aumiao.core.process import FileProcessor
 from aumiao.core.services import services
+from aumiao.utils.data import DEFAULT_PROFILE, PathConfig, ProfileError, has_legacy_data, migrate_legacy_data
 
 T = TypeVar("T")
 
@@ -463,9 +466,46 @@ def run_main_loop(menu_system: MenuSystem) -> None:
 		pause_for_continue()
 
 
+def parse_args(args: list[str]) -> Namespace:
+	"""解析命令行参数"""
+	parser = ArgumentParser(description="Aumiao 编程猫社区工具")
+	parser.add_argument("--data-dir", type=Path, help="数据目录, AUMIAO_DATA_DIR 环境变量优先; 缺省为平台数据目录")
+	return parser.parse_args(args)
+
+
+def offer_data_migration() -> None:
+	"""工作目录下存在旧版数据时询问是否复制到数据目录, 未经确认不会迁移"""
+	source, target = Path.cwd(), PathConfig.data_root()
+	if not has_legacy_data(source, target):
+		return
+	print(printer.color_text(f"检测到旧版数据目录 {source / 'data'}, 当前数据目录为 {target}", "COMMENT"))
+	if printer.prompt_input("是否将旧数据复制到当前数据目录? (y/n)").lower() == "y":
+		migrate_legacy_data(source, target)
+
+
 def main() -> None:
 	"""主程序入口 - 优化流程控制"""
 	enable_vt_mode()
+	# 数据目录必须在首次读取配置之前确定
+	args = parse_args(argv[1:])
+	if args.data_dir is not None:
+		PathConfig.set_data_dir(args.data_dir)
+	offer_data_migration()
+	# 配置无效时列出全部错误并以非零状态退出, 避免运行中出现难以理解的错误
+	# 客户端在首次使用时才创建, 校验必须在此之前完成
+	try:
+		errors = coordinator.setting_manager.validate()
+	except ProfileError as e:
+		print(printer.color_text(str(e), "ERROR"))
+		exits(1)
+	profile = coordinator.setting_manager.active_profile()
+	if profile != DEFAULT_PROFILE:
+		print(printer.color_text(f"当前配置档案: {profile}", "COMMENT"))
+	if errors:
+		for error in errors:
+			print(printer.color_text(f"配置项 {error.path} 无效: {error.message}", "ERROR"))
+		print(printer.color_text(f"setting.json 存在 {len(errors)} 处无效配置, 请修正后重新运行", "ERROR"))
+		exits(1)
 	Index().index()
 	account_data_manager = AccountDataManager()
 	menu_system = MenuSystem(account_data_manager)
@@ -477,6 +517,8 @@ def main() -> None:
 	except Exception:
 		handle_unexpected_error()
 	finally:
+		# 写入尚未保存的使用统计
+		coordinator.cache_manager.flush_usage()
 		prompt_exit()
 
 
@@ -0,0 +1,9 @@
+"""
+单元测试, 运行方式: python -m unittest discover -s test -t .
+测试期间的数据目录指向临时目录, 避免改写真实的 data/ 与 cache/
+"""
+
+from os import environ
+from tempfile import mkdtemp
+
+environ.setdefault("AUMIAO_DATA_DIR", mkdtemp(prefix="aumiao-test-"))
@@ -0,0 +1,220 @@
+import unittest
+from contextlib import redirect_stdout
+from io import StringIO
+from pathlib import Path
+from random import Random
+from tempfile import TemporaryDirectory
+from unittest.mock import patch
+
+from httpx import ConnectError, HTTPStatusError, ReadError, Request, Response
+
+from aumiao.utils.acquire import DEFAULT_HEADERS, BaseHTTPClient, ClientConfig, RetryBudgetExhausted, RetryEvent, SafeModeError
+from aumiao.utils.data import BaseManager
+from aumiao.utils.decorator import RetryBudget
+
+
+class FailingClient(BaseHTTPClient):
+	"""每次请求都抛出 ConnectError 并计数的客户端"""
+
+	def __init__(self, config: ClientConfig) -> None:
+		super().__init__(config)
+		self.calls = 0
+
+	def _execute_request(self, **_kwargs: object) -> object:
+		self.calls += 1
+		msg = f"failure {self.calls}"
+		raise ConnectError(msg)
+
+
+class RecordingClient(BaseHTTPClient):
+	"""记录请求并返回固定响应的客户端"""
+
+	def __init__(self, config: ClientConfig, response: Response) -> None:
+		super().__init__(config)
+		self.requests: list[tuple[str, str]] = []
+		self.response = response
+
+	def _execute_request(self, *, method: str, url: str, **_kwargs: object) -> Response:
+		self.requests.append((method, url))
+		return self.response
+
+
+class BuildHeadersTest(unittest.TestCase):
+	def test_user_headers_override_defaults(self) -> None:
+		headers = BaseHTTPClient.build_headers({"User-Agent": "aumiao", "X-Extra": "1"})
+		self.assertEqual(headers["User-Agent"], "aumiao")
+		self.assertEqual(headers["Accept"], DEFAULT_HEADERS["Accept"])
+		self.assertEqual(headers["X-Extra"], "1")
+
+	def test_rejects_invalid_names_and_values(self) -> None:
+		for headers in ({"Bad Name": "1"}, {"X-Line": "a\r\nb"}, {"X-Cjk": "编程猫"}):
+			with self.subTest(headers=headers), self.assertRaises(ValueError):
+				BaseHTTPClient.build_headers(headers)
+
+
+class RouteTemplateTest(unittest.TestCase):
+	def test_ids_and_query_are_collapsed(self) -> None:
+		self.assertEqual(BaseHTTPClient.route_template("/creation-tools/v1/works/12345?x=1"), "/creation-tools/v1/works/{id}")
+		self.assertEqual(BaseHTTPClient.route_template("/web/forums/posts/99/replies/7"), "/web/forums/posts/{id}/replies/{id}")
+		self.assertEqual(BaseHTTPClient.route_template("/tiger/v3/web/accounts/profile"), "/tiger/v3/web/accounts/profile")
+		self.assertEqual(BaseHTTPClient.route_template("/v2/works/0f8fad5b-d9cb-469f-a165-70867728950e"), "/v2/works/{id}")
+
+
+class HeadTest(unittest.TestCase):
+	def test_returns_headers_from_absolute_url(self) -> None:
+		client = RecordingClient(ClientConfig(), Response(200, headers={"Content-Length": "42"}, request=Request("HEAD", "https://example.com")))
+		headers = client.head("/creation-tools/v1/works/1", base_url_key="creation")
+		self.assertEqual(headers["Content-Length"], "42")
+		self.assertEqual(client.requests, [("HEAD", f"{client.config.get_base_url('creation')}/creation-tools/v1/works/1")])
+
+	def test_error_status_raises_with_absolute_request_url(self) -> None:
+		client = RecordingClient(ClientConfig(), Response(404, request=Request("HEAD", "https://example.com")))
+		with self.assertRaises(HTTPStatusError) as ctx:
+			client.head("/missing", params={"a": 1})
+		self.assertTrue(str(ctx.exception.request.url).startswith(f"{client.config.get_base_url()}/missing"))
+		self.assertEqual(ctx.exception.response.status_code, 404)
+
+
+class HealthCheckTest(unittest.TestCase):
+	def test_reachable_reports_status_code(self) -> None:
+		client = BaseHTTPClient(ClientConfig())
+		with patch.object(client._http_client, "request", return_value=Response(503)) as request:  # noqa: SLF001
+			status = client.health_check()
+		self.assertTrue(status.reachable)
+		self.assertEqual(status.status_code, 503)
+		self.assertEqual(request.call_args.args, ("GET", f"{client.config.get_base_url()}/coconut/clouddb/currentTime"))
+
+	def test_any_transport_error_is_unreachable(self) -> None:
+		client = BaseHTTPClient(ClientConfig())
+		with patch.object(client._http_client, "request", side_effect=ReadError("connection reset")):  # noqa: SLF001
+			status = client.health_check()
+		self.assertFalse(status.reachable)
+		self.assertEqual(status.status_code, 0)
+
+
+class SafeModeTest(unittest.TestCase):
+	def setUp(self) -> None:
+		self._tmp = TemporaryDirectory()
+		BaseManager.set_safe_mode(enabled=True)
+		self.client = RecordingClient(ClientConfig(), Response(200, request=Request("GET", "https://example.com")))
+		self.client.log_file = Path(self._tmp.name) / "requests.txt"
+
+	def tearDown(self) -> None:
+		BaseManager.set_safe_mode(enabled=None)
+		self._tmp.cleanup()
+
+	def test_mutating_request_is_blocked(self) -> None:
+		with redirect_stdout(StringIO()), self.assertRaises(SafeModeError):
+			self.client.send_request(endpoint="/nemo/v2/works/1/like", method="POST")
+		self.assertEqual(self.client.requests, [])
+		self.assertIn("safe_mode: blocked POST", self.client.log_file.read_text(encoding="utf-8"))
+
+	def test_read_request_goes_through(self) -> None:
+		response = self.client.send_request(endpoint="/nemo/v2/works/1", method="GET")
+		self.assertEqual(response.status_code, 200)
+		self.assertEqual(self.client.requests, [("GET", f"{self.client.config.get_base_url()}/nemo/v2/works/1")])
+
+
+class RetryDelaysTest(unittest.TestCase):
+	def test_exponential_by_default(self) -> None:
+		self.assertEqual(list(ClientConfig(retry_delay=1.0).retry_delays(4)), [1.0, 2.0, 4.0])
+
+	def test_fixed_and_max_delay(self) -> None:
+		self.assertEqual(list(ClientConfig(retry_delay=0.5, retry_backoff="fixed").retry_delays(3)), [0.5, 0.5])
+		self.assertEqual(list(ClientConfig(retry_delay=1.0, retry_max_delay=3.0).retry_delays(5, backoff_factor=1.0)), [1.0, 2.0, 3.0, 3.0])
+
+	def test_jitter_stays_within_range(self) -> None:
+		config = ClientConfig(retry_delay=1.0, retry_jitter=0.5)
+		delays = list(config.retry_delays(6, rng=Random(3)))
+		for delay, base in zip(delays, [1.0, 2.0, 4.0, 8.0, 16.0], strict=True):
+			self.assertTrue(base * 0.5 <= delay <= base)
+		self.assertEqual(delays, list(config.retry_delays(6, rng=Random(3))))
+
+	def test_invalid_config(self) -> None:
+		with self.assertRaises(ValueError):
+			ClientConfig(retry_delay=2.0, retry_max_delay=1.0)
+		with self.assertRaises(ValueError):
+			ClientConfig(retry_jitter=1.5)
+
+
+@patch("aumiao.utils.acquire.sleep", lambda _seconds: None)
+class RetryIfTest(unittest.TestCase):
+	def test_always_true_predicate_retries_every_attempt(self) -> None:
+		client = FailingClient(ClientConfig(retry_if=lambda _error: True))
+		with self.assertRaises(ConnectError):
+			client.send_request("GET", "/x", retries=3, log=False)
+		self.assertEqual(client.calls, 3)
+
+	def test_rejecting_predicate_stops_after_first_error(self) -> None:
+		seen: list[Exception] = []
+		client = FailingClient(ClientConfig(retry_if=lambda error: seen.append(error) or False))
+		with self.assertRaises(ConnectError) as context:
+			client.send_request("GET", "/x", retries=3, log=False)
+		self.assertEqual(client.calls, 1)
+		# 抛出的是原始异常, 调用方可以按类型处理
+		self.assertIs(context.exception, seen[0])
+
+
+@patch("aumiao.utils.acquire.sleep", lambda _seconds: None)
+class OnRetryTest(unittest.TestCase):
+	def test_hook_fires_retries_minus_one_times(self) -> None:
+		events: list[RetryEvent] = []
+		client = FailingClient(ClientConfig(on_retry=events.append, retry_delay=0.5, retry_backoff="fixed"))
+		with self.assertRaises(ConnectError):
+			client.send_request("GET", "/x", retries=4, log=False)
+		self.assertEqual([event.attempt for event in events], [1, 2, 3])
+		self.assertTrue(all(event.max_attempts == 4 and event.delay == 0.5 for event in events))
+		self.assertEqual(events[0].error, "failure 1")
+
+	def test_failing_hook_does_not_abort_retries(self) -> None:
+		def hook(_event: RetryEvent) -> None:
+			msg = "hook failed"
+			raise RuntimeError(msg)
+
+		client = FailingClient(ClientConfig(on_retry=hook))
+		with self.assertRaises(ConnectError):
+			client.send_request("GET", "/x", retries=3, log=False)
+		self.assertEqual(client.calls, 3)
+
+
+@patch("aumiao.utils.acquire.sleep", lambda _seconds: None)
+class RetryBudgetTest(unittest.TestCase):
+	def test_budget_replenishes_up_to_max(self) -> None:
+		budget = RetryBudget(ratio=0.5, initial_permits=1, max_permits=2)
+		self.assertTrue(budget.try_withdraw())
+		self.assertFalse(budget.try_withdraw())
+		for _ in range(10):
+			budget.record_success()
+		self.assertEqual(budget.available, 2)
+
+	def test_exhausted_budget_stops_retries_with_distinct_error(self) -> None:
+		client = FailingClient(ClientConfig(retry_budget=RetryBudget(ratio=0.5, initial_permits=2, max_permits=2)))
+		with self.assertRaises(RetryBudgetExhausted) as ctx:
+			client.send_request("GET", "/x", retries=5, log=False)
+		self.assertEqual(client.calls, 3)
+		self.assertIsInstance(ctx.exception.__cause__, ConnectError)
+		self.assertEqual(str(ctx.exception.__cause__), "failure 3")
+
+	def test_successes_let_retries_resume(self) -> None:
+		budget = RetryBudget(ratio=0.5, initial_permits=0, max_permits=2)
+		client = RecordingClient(ClientConfig(retry_budget=budget), Response(200, request=Request("GET", "https://example.com")))
+		failing = FailingClient(ClientConfig(retry_budget=budget))
+		with self.assertRaises(RetryBudgetExhausted):
+			failing.send_request("GET", "/x", retries=3, log=False)
+		self.assertEqual(failing.calls, 1)
+		for _ in range(2):
+			client.send_request("GET", "/ok", log=False)
+		self.assertEqual(budget.available, 1)
+		with self.assertRaises(RetryBudgetExhausted):
+			failing.send_request("GET", "/x", retries=3, log=False)
+		self.assertEqual(failing.calls, 3)
+
+	def test_last_attempt_does_not_need_budget(self) -> None:
+		budget = RetryBudget(initial_permits=0)
+		client = FailingClient(ClientConfig(retry_budget=budget))
+		with self.assertRaises(ConnectError):
+			client.send_request("GET", "/x", retries=1, log=False)
+
+
+if __name__ == "__main__":
+	unittest.main()
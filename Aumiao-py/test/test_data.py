@@ -0,0 +1,965 @@
+import unittest
+from contextlib import redirect_stdout
+from dataclasses import asdict
+from io import StringIO
+from json import dumps, loads
+from os import environ, utime
+from pathlib import Path
+from random import Random
+from tempfile import TemporaryDirectory
+from threading import Thread
+from typing import cast
+from time import mktime, monotonic, sleep
+from unittest.mock import patch
+
+from aumiao.utils import acquire
+from aumiao.utils.data import (
+	DEFAULT_PROFILE,
+	INCIDENT_KEEP_COUNT,
+	KEYRING_MARKER,
+	KEYRING_SERVICE,
+	PROFILE_ENV_VAR,
+	AccountData,
+	Answer,
+	BaseManager,
+	BlackRoomEntry,
+	CacheHistoryManager,
+	CacheManager,
+	CodeMaoCache,
+	CodeMaoData,
+	CodeMaoFile,
+	CodeMaoSetting,
+	CommentTemplate,
+	CredentialStore,
+	CredentialVault,
+	DataClassConverter,
+	DataManager,
+	FileTooLargeError,
+	JsonFileHandler,
+	PathConfig,
+	ProfileError,
+	SettingManager,
+	SettingValidator,
+	UserData,
+	default_data_root,
+	has_legacy_data,
+	migrate_legacy_data,
+	pick_template,
+)
+
+
+class DataRootTest(unittest.TestCase):
+	def test_env_override_wins(self) -> None:
+		self.assertEqual(default_data_root("linux", {"AUMIAO_DATA_DIR": "/srv/aumiao"}, Path("/home/u")), Path("/srv/aumiao"))
+
+	def test_env_then_flag_then_default(self) -> None:
+		home = Path("/home/u")
+		env = {"AUMIAO_DATA_DIR": "/srv/env"}
+		self.assertEqual(default_data_root("linux", env, home, override=Path("/srv/flag")), Path("/srv/env"))
+		self.assertEqual(default_data_root("linux", {}, home, override=Path("/srv/flag")), Path("/srv/flag"))
+		self.assertEqual(default_data_root("linux", {}, home), home / ".local" / "share" / "aumiao")
+
+	def test_platform_defaults(self) -> None:
+		home = Path("/home/u")
+		self.assertEqual(default_data_root("win32", {"APPDATA": "C:/Users/u/AppData/Roaming"}, home), Path("C:/Users/u/AppData/Roaming/Aumiao"))
+		self.assertEqual(default_data_root("darwin", {}, home), home / "Library" / "Application Support" / "Aumiao")
+		self.assertEqual(default_data_root("linux", {}, home), home / ".local" / "share" / "aumiao")
+		self.assertEqual(default_data_root("linux", {"XDG_DATA_HOME": "/xdg"}, home), Path("/xdg/aumiao"))
+
+	def test_migrate_copies_once(self) -> None:
+		with TemporaryDirectory() as source_dir, TemporaryDirectory() as target_dir:
+			source, target = Path(source_dir), Path(target_dir) / "aumiao"
+			(source / "data").mkdir()
+			(source / "data" / "data.json").write_text("{}")
+			(source / "cache").mkdir()
+			(source / "cache" / "info.json").write_text("{}")
+			self.assertEqual(migrate_legacy_data(source, target), [target / "data", target / "cache"])
+			self.assertTrue((target / "data" / "data.json").exists())
+			self.assertTrue((target / "cache" / "info.json").exists())
+			self.assertTrue((source / "data" / "data.json").exists())
+			self.assertEqual(migrate_legacy_data(source, target), [])
+
+	def test_migrate_without_legacy_data(self) -> None:
+		with TemporaryDirectory() as source_dir, TemporaryDirectory() as target_dir:
+			self.assertEqual(migrate_legacy_data(Path(source_dir), Path(target_dir)), [])
+
+	def test_empty_target_data_dir_still_offers_migration(self) -> None:
+		with TemporaryDirectory() as source_dir, TemporaryDirectory() as target_dir:
+			source, target = Path(source_dir), Path(target_dir)
+			(source / "data").mkdir()
+			(target / "data").mkdir()
+			self.assertTrue(has_legacy_data(source, target))
+			(target / "data" / "setting.json").write_text("{}")
+			self.assertFalse(has_legacy_data(source, target))
+
+
+@patch.multiple(PathConfig, _data_dir_override=None, _resolved_root=None)
+class PathConfigTest(unittest.TestCase):
+	def setUp(self) -> None:
+		self._tmp = TemporaryDirectory()
+		self.root = Path(self._tmp.name)
+
+	def tearDown(self) -> None:
+		self._tmp.cleanup()
+
+	def test_data_dir_setter_applies_before_first_access(self) -> None:
+		with patch.dict(environ, {"AUMIAO_DATA_DIR": ""}):
+			PathConfig.set_data_dir(self.root / "flag")
+			self.assertEqual(PathConfig.DATA_FILE_PATH, self.root / "flag" / "data" / "data.json")
+		self.assertTrue((self.root / "flag" / "data").is_dir())
+
+	def test_env_wins_over_setter(self) -> None:
+		with patch.dict(environ, {"AUMIAO_DATA_DIR": str(self.root / "env")}):
+			PathConfig.set_data_dir(self.root / "flag")
+			self.assertEqual(PathConfig().SETTING_FILE_PATH, self.root / "env" / "data" / "setting.json")
+
+	def test_setter_after_first_access_is_rejected(self) -> None:
+		with patch.dict(environ, {"AUMIAO_DATA_DIR": str(self.root)}):
+			self.assertEqual(PathConfig.CACHE_DIR, self.root / "cache")
+			with self.assertRaises(RuntimeError):
+				PathConfig.set_data_dir(self.root / "late")
+			self.assertEqual(PathConfig.data_root(), self.root)
+
+	def test_downloads_and_logs_stay_in_working_directory(self) -> None:
+		self.assertEqual(PathConfig.DOWNLOAD_DIR, Path.cwd() / "download")
+		self.assertEqual(PathConfig.LOG_DIR, Path.cwd() / "logs")
+		self.assertIsNone(PathConfig._resolved_root)  # noqa: SLF001
+
+
+class ListFilesTest(unittest.TestCase):
+	def setUp(self) -> None:
+		self._tmp = TemporaryDirectory()
+		self.root = Path(self._tmp.name)
+		(self.root / "nested" / "deep").mkdir(parents=True)
+		(self.root / "a.json").write_text("{}")
+		(self.root / "b.txt").write_text("text")
+		(self.root / "nested" / "c.json").write_text("[]")
+		(self.root / "nested" / "deep" / "d.json").write_text("1")
+		# 指向上级目录的符号链接, 形成环
+		(self.root / "nested" / "loop").symlink_to(self.root, target_is_directory=True)
+
+	def tearDown(self) -> None:
+		self._tmp.cleanup()
+
+	def relative(self, entries: list) -> list[str]:
+		return [entry.path.relative_to(self.root).as_posix() for entry in entries]
+
+	def test_top_level_only_by_default(self) -> None:
+		self.assertEqual(self.relative(CodeMaoFile.list_files(self.root, "*.json")), ["a.json"])
+
+	def test_recursive_glob_terminates_on_symlink_loop(self) -> None:
+		entries = CodeMaoFile.list_files(self.root, "*.json", recursive=True)
+		self.assertEqual(self.relative(entries), ["a.json", "nested/c.json", "nested/deep/d.json"])
+		self.assertEqual(entries[0].size, 2)
+
+	def test_sort_by_modified(self) -> None:
+		for offset, name in enumerate(["nested/c.json", "a.json"]):
+			path = self.root / name
+			mtime = 1_000_000 + offset
+			utime(path, (mtime, mtime))
+		entries = CodeMaoFile.list_files(self.root, "*.json", recursive=True, sort_by="modified")
+		self.assertEqual(self.relative(entries)[:2], ["nested/c.json", "a.json"])
+
+
+class ManagerLoadTest(unittest.TestCase):
+	def setUp(self) -> None:
+		self._tmp = TemporaryDirectory()
+		self.root = Path(self._tmp.name)
+
+	def tearDown(self) -> None:
+		self._tmp.cleanup()
+
+	def test_singleton_round_trip(self) -> None:
+		manager = DataManager()
+		manager.update({"ACCOUNT_DATA": {"nickname": "测试"}})
+		self.assertIs(DataManager(), manager)
+		loaded = JsonFileHandler.load_json_file(PathConfig.DATA_FILE_PATH, CodeMaoData)
+		self.assertEqual(loaded.ACCOUNT_DATA.nickname, "测试")
+		manager.reload()
+		self.assertEqual(manager.data.ACCOUNT_DATA.nickname, "测试")
+
+	def test_update_with_loses_no_concurrent_updates(self) -> None:
+		manager = DataManager()
+		manager.update_with(lambda data: (data.USER_DATA.ads.clear(), data.USER_DATA.black_room.clear()))
+
+		def append_many(name: str) -> None:
+			for index in range(50):
+				item = BlackRoomEntry(user_id=f"{name}-{index}") if name == "black_room" else f"{name}-{index}"
+				manager.update_with(lambda data, item=item: getattr(data.USER_DATA, name).append(item))
+
+		threads = [Thread(target=append_many, args=(name,)) for name in ("ads", "black_room")]
+		for thread in threads:
+			thread.start()
+		for thread in threads:
+			thread.join()
+		self.assertEqual(manager.read_with(lambda data: len(data.USER_DATA.ads)), 50)
+		self.assertEqual(manager.read_with(lambda data: len(data.USER_DATA.black_room)), 50)
+		saved = JsonFileHandler.load_json_file(PathConfig.DATA_FILE_PATH, CodeMaoData)
+		self.assertEqual((len(saved.USER_DATA.ads), len(saved.USER_DATA.black_room)), (50, 50))
+
+	def test_normalize_messy_user_data(self) -> None:
+		manager = DataManager()
+		messy = {"ads": [" b ", "a", "", "b"], "black_room": ["2", "1", "2"], "replies": ["later", " first ", "later"]}
+		manager.update({"USER_DATA": messy})
+		preview = manager.normalize(dry_run=True)
+		self.assertEqual(preview["ads"], ([" b ", "a", "", "b"], ["a", "b"]))
+		self.assertEqual(manager.data.USER_DATA.ads, [" b ", "a", "", "b"])
+		manager.normalize()
+		self.assertEqual(manager.data.USER_DATA.ads, ["a", "b"])
+		self.assertEqual([entry.user_id for entry in manager.data.USER_DATA.black_room], ["1", "2"])
+		# replies 与顺序有关, 只去重不排序
+		self.assertEqual(manager.data.USER_DATA.replies, [CommentTemplate(text="later"), CommentTemplate(text="first")])
+
+	def test_missing_file_uses_defaults(self) -> None:
+		path = self.root / "missing.json"
+		self.assertEqual(JsonFileHandler.load_json_file(path, CodeMaoData, create_if_missing=False), CodeMaoData())
+		self.assertFalse(path.exists())
+
+	def test_parse_error_keeps_corrupt_copy(self) -> None:
+		path = self.root / "broken.json"
+		path.write_text('{"ACCOUNT_DATA": ', encoding="utf-8")
+		self.assertEqual(JsonFileHandler.load_json_file(path, CodeMaoData), CodeMaoData())
+		corrupt = list(self.root.glob("broken.json.corrupt-*"))
+		self.assertEqual(len(corrupt), 1)
+		self.assertEqual(corrupt[0].read_text(encoding="utf-8"), '{"ACCOUNT_DATA": ')
+
+
+class BlackRoomTest(unittest.TestCase):
+	def setUp(self) -> None:
+		tmp = self.enterContext(TemporaryDirectory())
+		self.enterContext(patch.dict(environ, {"AUMIAO_DATA_DIR": tmp}))
+		self.enterContext(patch.multiple(PathConfig, _data_dir_override=None, _resolved_root=None))
+		self.enterContext(redirect_stdout(StringIO()))
+		self.manager = DataManager.__wrapped__()
+		self.manager.update_with(lambda data: data.USER_DATA.black_room.clear())
+
+	def saved_black_room(self) -> list:
+		return loads(PathConfig.DATA_FILE_PATH.read_text(encoding="utf-8"))["USER_DATA"]["black_room"]
+
+	def test_legacy_strings_are_migrated(self) -> None:
+		PathConfig.DATA_FILE_PATH.write_text(dumps({"USER_DATA": {"black_room": ["2233", 1742185446]}}), encoding="utf-8")
+		loaded = JsonFileHandler.load_json_file(PathConfig.DATA_FILE_PATH, CodeMaoData)
+		self.assertEqual(loaded.USER_DATA.black_room, [BlackRoomEntry(user_id="2233"), BlackRoomEntry(user_id="1742185446")])
+
+	def test_metadata_survives_round_trip(self) -> None:
+		entry = BlackRoomEntry(added_at=1700000000, nickname="刷屏", reason="广告", source="auto_spam", user_id="42")
+		self.assertTrue(self.manager.add_to_black_room(entry))
+		self.assertEqual(self.saved_black_room(), [{"added_at": 1700000000, "nickname": "刷屏", "reason": "广告", "source": "auto_spam", "user_id": "42"}])
+		self.manager.reload()
+		self.assertEqual(self.manager.data.USER_DATA.black_room, [entry])
+
+	def test_add_remove_and_lookup(self) -> None:
+		self.assertTrue(self.manager.add_to_black_room(BlackRoomEntry(user_id=" 7 ")))
+		self.assertFalse(self.manager.add_to_black_room(BlackRoomEntry(reason="重复", user_id="7")))
+		self.assertTrue(self.manager.is_blacklisted(7))
+		self.assertEqual(self.manager.blacklisted_ids(), {"7"})
+		self.assertGreater(self.manager.data.USER_DATA.black_room[0].added_at, 0)
+		with self.assertRaises(ValueError):
+			self.manager.add_to_black_room(BlackRoomEntry(user_id=" "))
+		self.assertTrue(self.manager.remove_from_black_room("7"))
+		self.assertFalse(self.manager.remove_from_black_room("7"))
+		self.assertFalse(self.manager.is_blacklisted("7"))
+
+	def test_normalize_dedupes_by_user_id(self) -> None:
+		entries = [{"reason": "first", "user_id": "2"}, {"user_id": " 1"}, {"reason": "second", "user_id": "2"}, {"user_id": ""}]
+		self.manager.update({"USER_DATA": {"black_room": entries}})
+		self.assertEqual(self.manager.normalize(dry_run=True)["black_room"], (["2", " 1", "2", ""], ["1", "2"]))
+		self.manager.normalize()
+		self.assertEqual(self.manager.data.USER_DATA.black_room, [BlackRoomEntry(user_id="1"), BlackRoomEntry(reason="first", user_id="2")])
+
+
+class AnswerTest(unittest.TestCase):
+	def test_find_answer_match_modes(self) -> None:
+		user_data = UserData(
+			answers=[
+				Answer(enabled=False, question="禁用"),
+				Answer(match_mode="exact", question="你好", responses=["exact"]),
+				Answer(question="你好", responses=["contains"]),
+				Answer(match_mode="regex", question=r"^作品\s*\d+$", responses=["regex"]),
+			],
+		)
+		cases = [
+			("你好", "exact"),
+			(" 你好 ", "exact"),
+			("你好呀", "contains"),
+			("作品 42", "regex"),
+			("看看作品 42", None),
+			("禁用了吗", None),
+			("无关内容", None),
+		]
+		for text, expected in cases:
+			with self.subTest(text=text):
+				answer = user_data.find_answer(text)
+				self.assertEqual(answer.responses[0] if answer else None, expected)
+
+	def test_coerce_shapes(self) -> None:
+		cases = [
+			({"牢大": "孩子们, 我回来了"}, [Answer(question="牢大", responses=["孩子们, 我回来了"])]),
+			({"只因": ["a", "b"]}, [Answer(question="只因", responses=["a", "b"])]),
+			({"a": "1", "b": ["2"]}, [Answer(question="a", responses=["1"]), Answer(question="b", responses=["2"])]),
+			({"match_mode": "exact", "question": "q", "responses": ["r"]}, [Answer(match_mode="exact", question="q", responses=["r"])]),
+			({"keyword": {"nested": 1}}, [Answer(enabled=False, raw={"keyword": {"nested": 1}})]),
+			("只有字符串", [Answer(enabled=False, raw={"value": "只有字符串"})]),
+		]
+		for value, expected in cases:
+			with self.subTest(value=value), redirect_stdout(StringIO()):
+				self.assertEqual(DataClassConverter.dict_to_dataclass(UserData, {"answers": [value]}).answers, expected)
+
+	def test_unknown_shape_is_logged_and_kept(self) -> None:
+		output = StringIO()
+		with redirect_stdout(output):
+			user_data = DataClassConverter.dict_to_dataclass(UserData, {"answers": [{"keyword": 1}]})
+		self.assertIn("无法识别的自动回复条目", output.getvalue())
+		self.assertEqual(asdict(user_data)["answers"][0]["raw"], {"keyword": 1})
+		# 保存后再次读取时 raw 原样保留
+		with redirect_stdout(StringIO()):
+			reloaded = DataClassConverter.dict_to_dataclass(UserData, asdict(user_data))
+		self.assertEqual(reloaded.answers, user_data.answers)
+
+	def test_invalid_regex_disables_entry(self) -> None:
+		output = StringIO()
+		with redirect_stdout(output):
+			user_data = DataClassConverter.dict_to_dataclass(
+				UserData,
+				{"answers": [{"match_mode": "regex", "question": "(未闭合", "responses": ["x"]}, {"question": "未闭合", "responses": ["y"]}]},
+			)
+		self.assertIn("无效", output.getvalue())
+		self.assertFalse(user_data.answers[0].enabled)
+		self.assertEqual(user_data.find_answer("(未闭合").responses, ["y"])
+
+
+class CommentTemplateTest(unittest.TestCase):
+	def test_strings_and_objects_are_accepted(self) -> None:
+		user_data = DataClassConverter.dict_to_dataclass(
+			UserData,
+			{"comments": ["666", {"created_at": 1700000000, "tags": ["夸奖"], "text": "不错", "weight": 2}], "replies": ["收到"]},
+		)
+		self.assertEqual(user_data.comments, [CommentTemplate(text="666"), CommentTemplate(created_at=1700000000, tags=["夸奖"], text="不错", weight=2.0)])
+		self.assertEqual(user_data.replies, [CommentTemplate(text="收到")])
+
+	def test_serialized_as_objects(self) -> None:
+		user_data = DataClassConverter.dict_to_dataclass(UserData, {"replies": ["收到"]})
+		self.assertEqual(asdict(user_data)["replies"], [{"created_at": None, "tags": [], "text": "收到", "weight": 1.0}])
+		self.assertEqual(DataClassConverter.dict_to_dataclass(UserData, asdict(user_data)), user_data)
+
+	def test_normalize_keeps_first_metadata(self) -> None:
+		user_data = UserData(replies=[CommentTemplate(tags=["a"], text=" 你好 "), CommentTemplate(tags=["b"], text="你好"), CommentTemplate(text=" ")])
+		self.assertEqual(DataManager._normalize_lists(user_data), {"replies": ([" 你好 ", "你好", " "], ["你好"])})  # noqa: SLF001
+		self.assertEqual(user_data.replies, [CommentTemplate(tags=["a"], text="你好")])
+
+	def test_pick_template_respects_weights(self) -> None:
+		templates = [CommentTemplate(text="never", weight=0), CommentTemplate(text="always", weight=1)]
+		rng = Random(0)
+		self.assertEqual({pick_template(templates, rng).text for _ in range(20)}, {"always"})
+		self.assertIsNone(pick_template([CommentTemplate(text="x", weight=0)]))
+		self.assertIsNone(pick_template([]))
+
+
+class CacheHistoryTest(unittest.TestCase):
+	def setUp(self) -> None:
+		self._tmp = TemporaryDirectory()
+		self.manager = CacheHistoryManager.__wrapped__(file_path=Path(self._tmp.name) / "info_history.jsonl")
+
+	def tearDown(self) -> None:
+		self._tmp.cleanup()
+
+	def append_series(self, count: int, start: int = 1_700_000_000, step: int = 3600) -> None:
+		for index in range(count):
+			self.manager.append(CodeMaoCache(fans=index, timestamp=start + index * step))
+
+	def test_range_queries(self) -> None:
+		self.append_series(10)
+		history = self.manager.history(1_700_000_000 + 2 * 3600, 1_700_000_000 + 5 * 3600)
+		self.assertEqual([snapshot.fans for snapshot in history], [2, 3, 4, 5])
+		latest = self.manager.latest_before(1_700_000_000 + 3 * 3600 + 1)
+		self.assertIsNotNone(latest)
+		self.assertEqual(latest.fans, 3)
+		self.assertIsNone(self.manager.latest_before(0))
+
+	def test_identical_consecutive_snapshots_are_skipped(self) -> None:
+		self.assertTrue(self.manager.append(CodeMaoCache(fans=1, timestamp=1)))
+		self.assertFalse(self.manager.append(CodeMaoCache(fans=1, timestamp=2)))
+		self.assertTrue(self.manager.append(CodeMaoCache(fans=2, timestamp=3)))
+		self.assertEqual(len(self.manager.history()), 2)
+
+	def test_prune_by_count_and_age(self) -> None:
+		self.manager.max_entries = 5
+		self.append_series(8)
+		self.assertEqual([snapshot.fans for snapshot in self.manager.history()], [3, 4, 5, 6, 7])
+		self.manager.max_age_days = 1
+		self.append_series(1, start=1_700_000_000 + 30 * 3600)
+		self.assertEqual([snapshot.fans for snapshot in self.manager.history()], [6, 7, 0])
+
+	def test_append_does_not_rewrite_until_limit_exceeded(self) -> None:
+		self.manager.max_entries = 3
+		with patch.object(CacheHistoryManager.__wrapped__, "_save", autospec=True) as save:
+			self.append_series(3)
+			save.assert_not_called()
+			self.assertEqual(len(self.manager._file_path.read_text(encoding="utf-8").splitlines()), 3)
+			self.append_series(1, start=1_700_100_000)
+			save.assert_called_once()
+
+	def test_report_needs_two_snapshots(self) -> None:
+		self.append_series(1)
+		self.assertFalse(self.manager.report())
+		self.manager.append(CodeMaoCache(fans=5, timestamp=1_700_100_000))
+		self.assertTrue(self.manager.report())
+
+
+class BomTest(unittest.TestCase):
+	def setUp(self) -> None:
+		self._tmp = TemporaryDirectory()
+		self.root = Path(self._tmp.name)
+
+	def tearDown(self) -> None:
+		self._tmp.cleanup()
+
+	def test_load_json_file_accepts_bom(self) -> None:
+		path = self.root / "data.json"
+		path.write_text(dumps({"ACCOUNT_DATA": {"nickname": "编程猫"}}, ensure_ascii=False), encoding="utf-8-sig")
+		self.assertTrue(path.read_bytes().startswith(b"\xef\xbb\xbf"))
+		self.assertEqual(JsonFileHandler.load_json_file(path, CodeMaoData).ACCOUNT_DATA.nickname, "编程猫")
+		# 读取成功, 不应产生损坏文件的备份
+		self.assertEqual(JsonFileHandler.list_backups(path), [])
+
+	def test_setting_with_bom_validates(self) -> None:
+		path = self.root / "setting.json"
+		path.write_text(dumps({"PARAMETER": {"spam_del_max": 5}}), encoding="utf-8-sig")
+		with redirect_stdout(StringIO()) as output:
+			manager = SettingManager.__wrapped__(path)
+			self.assertEqual(manager.data.PARAMETER.spam_del_max, 5)
+			self.assertEqual(manager.validate(), [])
+		self.assertNotIn("未知配置项", output.getvalue())
+
+
+class RecoveryTest(unittest.TestCase):
+	def setUp(self) -> None:
+		self._tmp = TemporaryDirectory()
+		self.path = Path(self._tmp.name) / f"recovery-{self.id().rsplit('.', 1)[-1]}.json"
+
+	def tearDown(self) -> None:
+		for backup in JsonFileHandler.list_backups(self.path):
+			backup.unlink()
+		self._tmp.cleanup()
+
+	def save(self, nickname: str) -> None:
+		JsonFileHandler.save_json_file(self.path, CodeMaoData(ACCOUNT_DATA=AccountData(nickname=nickname)))
+
+	def test_truncated_file_without_backup_uses_defaults(self) -> None:
+		self.path.write_text('{"ACCOUNT_DATA": {"nick', encoding="utf-8")
+		self.assertEqual(JsonFileHandler.load_json_file(self.path, CodeMaoData), CodeMaoData())
+		incident = JsonFileHandler.recover_report()[-1]
+		self.assertEqual(incident.file, self.path.name)
+		self.assertIsNone(incident.restored_from)
+		self.assertIsNotNone(incident.corrupt_path)
+		self.assertTrue(incident.corrupt_path.exists())
+
+	def test_garbage_file_restores_newest_backup(self) -> None:
+		self.save("first")
+		self.save("second")
+		self.save("third")
+		self.path.write_bytes(b"\x00\xff garbage")
+		loaded = JsonFileHandler.load_json_file(self.path, CodeMaoData)
+		self.assertEqual(loaded.ACCOUNT_DATA.nickname, "second")
+		incident = JsonFileHandler.recover_report()[-1]
+		self.assertEqual(incident.restored_from, JsonFileHandler.list_backups(self.path)[0])
+		# 恢复后原文件已被备份内容替换
+		self.assertEqual(JsonFileHandler.load_json_file(self.path, CodeMaoData).ACCOUNT_DATA.nickname, "second")
+
+	def test_unreadable_backups_are_skipped(self) -> None:
+		self.save("first")
+		self.save("second")
+		self.save("third")
+		JsonFileHandler.list_backups(self.path)[0].write_text("not json", encoding="utf-8")
+		self.path.write_text("{", encoding="utf-8")
+		self.assertEqual(JsonFileHandler.load_json_file(self.path, CodeMaoData).ACCOUNT_DATA.nickname, "first")
+
+	def test_backups_are_rotated(self) -> None:
+		for index in range(8):
+			self.save(str(index))
+		self.assertEqual(len(JsonFileHandler.list_backups(self.path)), 5)
+
+	def test_same_name_in_other_directory_has_separate_backups(self) -> None:
+		self.save("first")
+		self.save("second")
+		with TemporaryDirectory() as other_dir:
+			other = Path(other_dir) / self.path.name
+			JsonFileHandler.save_json_file(other, CodeMaoData())
+			JsonFileHandler.save_json_file(other, CodeMaoData())
+			other_backups = JsonFileHandler.list_backups(other)
+			self.assertEqual(len(other_backups), 1)
+			self.assertNotIn(other_backups[0], JsonFileHandler.list_backups(self.path))
+			other_backups[0].unlink()
+		self.assertEqual(len(JsonFileHandler.list_backups(self.path)), 1)
+
+	def test_legacy_backups_inside_data_dir_are_listed(self) -> None:
+		path = PathConfig.DATA_DIR / "legacy-backup.json"
+		legacy = PathConfig.BACKUP_DIR / f"{path.name}.1000"
+		PathConfig.BACKUP_DIR.mkdir(parents=True, exist_ok=True)
+		legacy.write_text("{}", encoding="utf-8")
+		try:
+			self.assertEqual(JsonFileHandler.list_backups(path), [legacy])
+		finally:
+			legacy.unlink()
+
+	def test_incidents_are_recorded_in_settings(self) -> None:
+		self.path.write_text("{", encoding="utf-8")
+		JsonFileHandler.load_json_file(self.path, CodeMaoData)
+		record = SettingManager().data.PROGRAM.INCIDENTS[-1]
+		self.assertEqual(record.file, self.path.name)
+		self.assertEqual(record.corrupt_path, str(JsonFileHandler.recover_report()[-1].corrupt_path))
+		self.assertEqual(record.restored_from, "")
+		saved = loads(PathConfig.SETTING_FILE_PATH.read_text(encoding="utf-8"))
+		self.assertEqual(saved["PROGRAM"]["INCIDENTS"][-1]["file"], self.path.name)
+
+	def test_incident_list_is_capped(self) -> None:
+		for _ in range(INCIDENT_KEEP_COUNT + 3):
+			self.path.write_text("{", encoding="utf-8")
+			JsonFileHandler.load_json_file(self.path, CodeMaoData)
+		self.assertEqual(len(SettingManager().data.PROGRAM.INCIDENTS), INCIDENT_KEEP_COUNT)
+
+
+class FileSizeLimitTest(unittest.TestCase):
+	def setUp(self) -> None:
+		self._tmp = TemporaryDirectory()
+		self.root = Path(self._tmp.name)
+		self.parameter = SettingManager().data.PARAMETER
+		self.parameter.max_data_file_bytes = 32
+
+	def tearDown(self) -> None:
+		self.parameter.max_data_file_bytes = 0
+		self._tmp.cleanup()
+
+	def test_oversized_json_is_rejected_without_recovery(self) -> None:
+		path = self.root / "big.json"
+		path.write_text('{"INFO": {"key": "' + "x" * 64 + '"}}', encoding="utf-8")
+		self.assertEqual(CodeMaoFile.file_size(path), path.stat().st_size)
+		with self.assertRaises(FileTooLargeError):
+			JsonFileHandler.load_json_file(path, CodeMaoData)
+		self.assertTrue(path.exists())
+		self.assertEqual(list(self.root.glob("big.json.corrupt-*")), [])
+
+	def test_small_json_loads(self) -> None:
+		path = self.root / "small.json"
+		path.write_text('{"INFO": {"k": "v"}}', encoding="utf-8")
+		self.assertEqual(JsonFileHandler.load_json_file(path, CodeMaoData).INFO, {"k": "v"})
+
+	def test_gzip_limit(self) -> None:
+		path = self.root / "big.json.gz"
+		CodeMaoFile.file_write(path, {"key": "x" * 64})
+		with self.assertRaises(FileTooLargeError):
+			CodeMaoFile.file_load_gz(path)
+		self.assertEqual(CodeMaoFile.file_load_gz(path, max_bytes=0), {"key": "x" * 64})
+
+
+class SafeModeTest(unittest.TestCase):
+	def setUp(self) -> None:
+		BaseManager.set_safe_mode(enabled=True)
+
+	def tearDown(self) -> None:
+		BaseManager.set_safe_mode(enabled=None)
+		DataManager().reload()
+
+	def test_save_skips_write_without_leaking_values(self) -> None:
+		manager = DataManager()
+		before = PathConfig.DATA_FILE_PATH.read_text(encoding="utf-8")
+		output = StringIO()
+		with redirect_stdout(output):
+			manager.update({"ACCOUNT_DATA": {"password": "hunter2"}})
+		self.assertEqual(PathConfig.DATA_FILE_PATH.read_text(encoding="utf-8"), before)
+		self.assertIn("ACCOUNT_DATA", output.getvalue())
+		self.assertNotIn("hunter2", output.getvalue())
+
+	def test_missing_file_is_not_created(self) -> None:
+		with TemporaryDirectory() as tmp:
+			path = Path(tmp) / "created.json"
+			self.assertEqual(JsonFileHandler.load_json_file(path, CodeMaoData), CodeMaoData())
+			self.assertFalse(path.exists())
+
+	def test_corrupt_file_is_left_in_place(self) -> None:
+		with TemporaryDirectory() as tmp:
+			path = Path(tmp) / "safe-corrupt.json"
+			path.write_text('{"ACCOUNT_DATA": {"nick', encoding="utf-8")
+			with redirect_stdout(StringIO()):
+				self.assertEqual(JsonFileHandler.load_json_file(path, CodeMaoData), CodeMaoData())
+			self.assertEqual(path.read_text(encoding="utf-8"), '{"ACCOUNT_DATA": {"nick')
+			self.assertEqual([entry.name for entry in Path(tmp).iterdir()], [path.name])
+			self.assertIsNone(JsonFileHandler.recover_report()[-1].corrupt_path)
+
+	def test_regenerate_is_skipped(self) -> None:
+		with TemporaryDirectory() as tmp:
+			path = Path(tmp) / "setting.json"
+			path.write_text('{"PARAMETER": {"spam_del_max": 99}}', encoding="utf-8")
+			with redirect_stdout(StringIO()):
+				self.assertIsNone(SettingManager.__wrapped__(path).regenerate())
+			self.assertEqual(path.read_text(encoding="utf-8"), '{"PARAMETER": {"spam_del_max": 99}}')
+			self.assertEqual([entry.name for entry in Path(tmp).iterdir()], ["setting.json"])
+
+
+@patch.multiple(PathConfig, _data_dir_override=None, _resolved_root=None)
+class DefaultSettingTest(unittest.TestCase):
+	def setUp(self) -> None:
+		# 在 test/__init__.py 设置的临时数据目录下使用一个全新的子目录
+		self.root = Path(environ["AUMIAO_DATA_DIR"]) / f"fresh-{self._testMethodName}"
+		self._env = patch.dict(environ, {"AUMIAO_DATA_DIR": str(self.root)})
+		self._env.start()
+
+	def tearDown(self) -> None:
+		self._env.stop()
+
+	def test_fresh_data_dir_gets_valid_default_setting(self) -> None:
+		self.assertFalse(PathConfig.SETTING_FILE_PATH.exists())
+		with redirect_stdout(StringIO()) as output:
+			manager = SettingManager.__wrapped__()
+			setting = manager.data
+		path = self.root / "data" / "setting.json"
+		self.assertIn(str(path), output.getvalue())
+		raw = loads(path.read_text(encoding="utf-8"))
+		self.assertEqual(list(raw), ["PARAMETER", "PROGRAM"])
+		self.assertEqual(SettingValidator.find_unknown_keys(CodeMaoSetting, raw), [])
+		self.assertEqual(SettingValidator.validate(setting), [])
+		self.assertIn("User-Agent", setting.PROGRAM.HEADERS)
+		self.assertGreater(setting.PARAMETER.report_work_max, 0)
+		# 带缩进的格式, 便于手动编辑
+		self.assertIn('\n    "PARAMETER"', path.read_text(encoding="utf-8"))
+
+	def test_regenerate_backs_up_existing_file(self) -> None:
+		path = self.root / "data" / "setting.json"
+		path.parent.mkdir(parents=True)
+		path.write_text('{"PARAMETER": {"spam_del_max": 99}}', encoding="utf-8")
+		with redirect_stdout(StringIO()):
+			backup = SettingManager.__wrapped__().regenerate()
+		self.assertIsNotNone(backup)
+		self.assertEqual(backup.read_text(encoding="utf-8"), '{"PARAMETER": {"spam_del_max": 99}}')
+		self.assertEqual(loads(path.read_text(encoding="utf-8"))["PARAMETER"]["spam_del_max"], 3)
+
+
+@patch.multiple(PathConfig, _data_dir_override=None, _resolved_root=None)
+class CacheFreshnessTest(unittest.TestCase):
+	def setUp(self) -> None:
+		self._tmp = TemporaryDirectory()
+		self._env = patch.dict(environ, {"AUMIAO_DATA_DIR": self._tmp.name})
+		self._env.start()
+		self.manager = CacheManager.__wrapped__()
+
+	def tearDown(self) -> None:
+		self._env.stop()
+		self._tmp.cleanup()
+
+	def test_is_stale(self) -> None:
+		self.assertTrue(CodeMaoCache().is_stale(60, now=1000))
+		self.assertFalse(CodeMaoCache(timestamp=1000).is_stale(60, now=1060))
+		self.assertTrue(CodeMaoCache(timestamp=1000).is_stale(60, now=1061))
+		# 时间戳位于未来 (时钟偏差) 视为过期
+		self.assertTrue(CodeMaoCache(timestamp=2000).is_stale(60, now=1000))
+
+	def test_get_fresh_refreshes_only_when_stale(self) -> None:
+		payload = {"fans": 1}
+		calls: list[dict] = []
+
+		def refresher() -> dict:
+			calls.append(payload)
+			return payload
+
+		clock = [1000.0]
+		cache = self.manager.get_fresh(refresher, max_age=60, now=lambda: clock[0])
+		self.assertEqual((cache.fans, cache.timestamp), (1, 1000))
+		# refresher 返回的字典不应被写入时间戳
+		self.assertEqual(payload, {"fans": 1})
+		clock[0] = 1030.0
+		self.manager.get_fresh(refresher, max_age=60, now=lambda: clock[0])
+		self.assertEqual(len(calls), 1)
+		clock[0] = 1100.0
+		payload["fans"] = 2
+		cache = self.manager.get_fresh(refresher, max_age=60, now=lambda: clock[0])
+		self.assertEqual((cache.fans, cache.timestamp, len(calls)), (2, 1100, 2))
+
+
+class UsageTest(unittest.TestCase):
+	# 2024-03-15 12:00 (本地时间) 及其后一个月、一年
+	DAY = mktime((2024, 3, 15, 12, 0, 0, 0, 0, -1))
+	NEXT_MONTH = mktime((2024, 4, 15, 12, 0, 0, 0, 0, -1))
+	NEXT_YEAR = mktime((2025, 1, 15, 12, 0, 0, 0, 0, -1))
+
+	def setUp(self) -> None:
+		tmp = self.enterContext(TemporaryDirectory())
+		self.enterContext(patch.dict(environ, {"AUMIAO_DATA_DIR": tmp}))
+		self.enterContext(patch.multiple(PathConfig, _data_dir_override=None, _resolved_root=None))
+		self.enterContext(redirect_stdout(StringIO()))
+		self.enterContext(patch.object(SettingManager().data.PARAMETER, "usage_stats", True))
+		self.manager = CacheManager.__wrapped__()
+		self.addCleanup(self.manager._usage_debouncer.cancel)  # noqa: SLF001
+
+	def saved_usage(self) -> dict:
+		if not PathConfig.CACHE_FILE_PATH.exists():
+			return {}
+		return loads(PathConfig.CACHE_FILE_PATH.read_text(encoding="utf-8")).get("usage", {})
+
+	def test_disabled_increments_are_noops(self) -> None:
+		SettingManager().data.PARAMETER.usage_stats = False
+		self.manager.increment("comments_deleted", now=self.DAY)
+		self.manager.flush_usage()
+		self.assertEqual(self.manager.usage_summary(), {})
+		self.assertEqual(self.saved_usage(), {})
+
+	def test_increments_are_buffered_until_flush(self) -> None:
+		for _ in range(3):
+			self.manager.increment("comments_deleted", now=self.DAY)
+		self.manager.increment("replies_sent", by=2, now=self.DAY)
+		self.assertEqual(self.saved_usage(), {})
+		self.assertEqual(self.manager.usage_summary(now=self.DAY), {"comments_deleted": 3, "replies_sent": 2})
+		self.manager.flush_usage()
+		self.assertEqual(self.saved_usage(), {"2024-03-15": {"comments_deleted": 3, "replies_sent": 2}})
+		self.manager.increment("comments_deleted", now=self.DAY)
+		self.manager.flush_usage()
+		self.assertEqual(self.saved_usage(), {"2024-03-15": {"comments_deleted": 4, "replies_sent": 2}})
+
+	def test_summary_periods(self) -> None:
+		for moment in (self.DAY, self.NEXT_MONTH, self.NEXT_YEAR):
+			self.manager.increment("comments_deleted", now=moment)
+		self.manager.flush_usage()
+		self.assertEqual(self.manager.usage_summary("day", now=self.DAY), {"comments_deleted": 1})
+		self.assertEqual(self.manager.usage_summary("month", now=self.NEXT_MONTH), {"comments_deleted": 1})
+		self.assertEqual(self.manager.usage_summary("year", now=self.NEXT_MONTH), {"comments_deleted": 2})
+		self.assertEqual(self.manager.usage_summary("all"), {"comments_deleted": 3})
+		self.assertEqual(self.manager.usage_summary("day", now=self.NEXT_YEAR + 86400), {})
+
+	def test_debounced_flush_writes_counters(self) -> None:
+		with patch("aumiao.utils.data.USAGE_FLUSH_SECONDS", 0.01):
+			manager = CacheManager.__wrapped__()
+		self.addCleanup(manager._usage_debouncer.cancel)  # noqa: SLF001
+		manager.increment("replies_sent", now=self.DAY)
+		deadline = monotonic() + 2
+		while not self.saved_usage() and monotonic() < deadline:
+			sleep(0.01)
+		self.assertEqual(self.saved_usage(), {"2024-03-15": {"replies_sent": 1}})
+
+	def test_snapshots_do_not_copy_usage(self) -> None:
+		history = CacheHistoryManager.__wrapped__(file_path=Path(PathConfig.CACHE_FILE_PATH).with_name("history.jsonl"))
+		history.append(CodeMaoCache(fans=1, timestamp=1, usage={"2024-03-15": {"replies_sent": 1}}))
+		self.assertEqual(history.history()[0].usage, {})
+
+	def test_client_never_reads_usage(self) -> None:
+		# 使用统计只保存在本地, 发送请求的模块不应读取
+		source = Path(acquire.__file__).read_text(encoding="utf-8")
+		self.assertNotIn("usage", source)
+		self.assertNotIn("CacheManager", source)
+
+
+class HotReloadTest(unittest.TestCase):
+	def setUp(self) -> None:
+		self._tmp = TemporaryDirectory()
+		self.path = Path(self._tmp.name) / "setting.json"
+		self.stamp = 1_700_000_000
+		self.write({"PARAMETER": {"spam_del_max": 3}})
+		self.manager = SettingManager.__wrapped__(self.path)
+		self.assertEqual(self.manager.data.PARAMETER.spam_del_max, 3)
+		self.manager.enable_hot_reload()
+		self.received: list[int] = []
+		self.manager.add_reload_listener(lambda setting: self.received.append(setting.PARAMETER.spam_del_max))
+
+	def tearDown(self) -> None:
+		self._tmp.cleanup()
+
+	def write(self, data: dict) -> None:
+		self.path.write_text(dumps(data), encoding="utf-8")
+		# 显式推进修改时间, 不依赖文件系统的时间精度
+		self.stamp += 10
+		utime(self.path, (self.stamp, self.stamp))
+
+	def test_edited_file_is_visible_without_recreating_manager(self) -> None:
+		self.write({"PARAMETER": {"spam_del_max": 5}})
+		with redirect_stdout(StringIO()):
+			self.assertEqual(self.manager.data.PARAMETER.spam_del_max, 5)
+		self.assertEqual(self.received, [5])
+		# 未再修改时不会重复通知
+		self.assertEqual(self.manager.data.PARAMETER.spam_del_max, 5)
+		self.assertEqual(self.received, [5])
+
+	def test_invalid_edit_keeps_previous_setting(self) -> None:
+		self.write({"PARAMETER": {"spam_del_max": 7}, "PROGRAM": {"HEADERS": {"X-Token": "${AUMIAO_TEST_UNDEFINED}"}}})
+		with redirect_stdout(StringIO()) as output:
+			self.assertEqual(self.manager.data.PARAMETER.spam_del_max, 3)
+		self.assertIn("AUMIAO_TEST_UNDEFINED", output.getvalue())
+		self.assertEqual(self.received, [])
+
+	def test_failing_listener_does_not_skip_others(self) -> None:
+		def broken(_setting: CodeMaoSetting) -> None:
+			msg = "listener failed"
+			raise ValueError(msg)
+
+		later: list[int] = []
+		self.manager.add_reload_listener(broken)
+		self.manager.add_reload_listener(lambda setting: later.append(setting.PARAMETER.spam_del_max))
+		self.write({"PARAMETER": {"spam_del_max": 9}})
+		with redirect_stdout(StringIO()) as output:
+			self.assertEqual(self.manager.data.PARAMETER.spam_del_max, 9)
+		self.assertEqual((self.received, later), ([9], [9]))
+		self.assertIn("listener failed", output.getvalue())
+
+
+class ProfileTest(unittest.TestCase):
+	def setUp(self) -> None:
+		self.root = Path(self.enterContext(TemporaryDirectory()))
+		self.path = self.root / "setting.json"
+		self.path.write_text(dumps({"PARAMETER": {"report_work_max": 8, "spam_del_max": 3}, "PROGRAM": {"HEADERS": {"A": "1", "B": "2"}}}), encoding="utf-8")
+		self.write_profile("testing", {"PARAMETER": {"spam_del_max": 9}, "PROGRAM": {"HEADERS": {"B": "override"}}})
+		self.output = self.enterContext(redirect_stdout(StringIO()))
+		self.enterContext(patch.dict(environ, {}))
+		environ.pop(PROFILE_ENV_VAR, None)
+
+	def write_profile(self, name: str, data: dict) -> None:
+		(self.root / f"setting.{name}.json").write_text(dumps(data), encoding="utf-8")
+
+	def test_profile_overrides_base(self) -> None:
+		environ[PROFILE_ENV_VAR] = "testing"
+		manager = SettingManager.__wrapped__(self.path)
+		self.assertEqual(manager.active_profile(), "testing")
+		self.assertEqual((manager.data.PARAMETER.spam_del_max, manager.data.PARAMETER.report_work_max), (9, 8))
+		self.assertEqual(manager.data.PROGRAM.HEADERS, {"A": "1", "B": "override"})
+
+	def test_switching_profiles(self) -> None:
+		manager = SettingManager.__wrapped__(self.path)
+		received: list[int] = []
+		manager.add_reload_listener(lambda setting: received.append(setting.PARAMETER.spam_del_max))
+		self.assertEqual((manager.active_profile(), manager.data.PARAMETER.spam_del_max), (DEFAULT_PROFILE, 3))
+		manager.use_profile("testing")
+		self.assertEqual((manager.active_profile(), manager.data.PARAMETER.spam_del_max), ("testing", 9))
+		manager.use_profile(DEFAULT_PROFILE)
+		self.assertEqual(manager.data.PARAMETER.spam_del_max, 3)
+		self.assertEqual(received, [9, 3])
+
+	def test_missing_or_invalid_profile_keeps_current(self) -> None:
+		manager = SettingManager.__wrapped__(self.path)
+		manager.use_profile("testing")
+		self.write_profile("broken", {"PARAMETER": {"spam_del_max": -1}})
+		for name, message in (("missing", "不存在"), ("broken", "spam_del_max"), ("../testing", "无效")):
+			with self.subTest(name=name), self.assertRaisesRegex(ProfileError, message):
+				manager.use_profile(name)
+		self.assertEqual((manager.active_profile(), manager.data.PARAMETER.spam_del_max), ("testing", 9))
+
+	def test_missing_profile_from_env_errors_clearly(self) -> None:
+		environ[PROFILE_ENV_VAR] = "missing"
+		manager = SettingManager.__wrapped__(self.path)
+		with self.assertRaisesRegex(ProfileError, "setting.missing.json"):
+			_ = manager.data
+
+	def test_save_keeps_profile_values_out_of_base_file(self) -> None:
+		manager = SettingManager.__wrapped__(self.path)
+		manager.use_profile("testing")
+		manager.update_with(lambda setting: setattr(setting.PARAMETER, "report_work_max", 4))
+		saved = loads(self.path.read_text(encoding="utf-8"))
+		self.assertEqual((saved["PARAMETER"]["spam_del_max"], saved["PARAMETER"]["report_work_max"]), (3, 4))
+		self.assertEqual(saved["PROGRAM"]["HEADERS"], {"A": "1", "B": "2"})
+		self.assertEqual(manager.data.PARAMETER.spam_del_max, 9)
+
+	def test_hot_reload_picks_up_profile_edits(self) -> None:
+		manager = SettingManager.__wrapped__(self.path)
+		manager.use_profile("testing")
+		manager.enable_hot_reload()
+		self.write_profile("testing", {"PARAMETER": {"spam_del_max": 11}})
+		stamp = manager._current_mtime() + 10  # noqa: SLF001
+		utime(self.root / "setting.testing.json", (stamp, stamp))
+		self.assertEqual(manager.data.PARAMETER.spam_del_max, 11)
+		self.assertIn("配置档案: testing", self.output.getvalue())
+
+
+class FakeKeyring:
+	"""内存中的钥匙串后端"""
+
+	def __init__(self) -> None:
+		self.secrets: dict[tuple[str, str], str] = {}
+
+	def get_password(self, service_name: str, username: str) -> str | None:
+		return self.secrets.get((service_name, username))
+
+	def set_password(self, service_name: str, username: str, password: str) -> None:
+		self.secrets[service_name, username] = password
+
+	def delete_password(self, service_name: str, username: str) -> None:
+		del self.secrets[service_name, username]
+
+
+class CredentialStoreTest(unittest.TestCase):
+	def setUp(self) -> None:
+		tmp = self.enterContext(TemporaryDirectory())
+		self.enterContext(patch.dict(environ, {"AUMIAO_DATA_DIR": tmp}))
+		# 类装饰器形式的 patch 不作用于 setUp, 这里在 setUp 中就需要使用临时数据目录
+		self.enterContext(patch.multiple(PathConfig, _data_dir_override=None, _resolved_root=None))
+		self.output = self.enterContext(redirect_stdout(StringIO()))
+		self.keyring = FakeKeyring()
+		CredentialVault.set_backend(self.keyring)
+		SettingManager().data.PARAMETER.credential_store = "keyring"
+		self.manager = DataManager.__wrapped__()
+		self.manager.update({"ACCOUNT_DATA": {"identity": "cat", "password": "secret"}})
+
+	def tearDown(self) -> None:
+		SettingManager().update_with(lambda setting: setattr(setting.PARAMETER, "credential_store", "file"))
+		CredentialVault.set_backend(None)
+		CredentialVault._warned = False  # noqa: SLF001
+
+	def saved_password(self) -> str:
+		return loads(PathConfig.DATA_FILE_PATH.read_text(encoding="utf-8"))["ACCOUNT_DATA"]["password"]
+
+	def test_keyring_store_keeps_only_a_marker_in_the_file(self) -> None:
+		self.assertEqual(self.saved_password(), KEYRING_MARKER)
+		self.assertEqual(self.keyring.secrets, {(KEYRING_SERVICE, "cat"): "secret"})
+		self.assertEqual(self.manager.data.ACCOUNT_DATA.password, "secret")
+		self.manager.reload()
+		self.assertEqual(self.manager.data.ACCOUNT_DATA.password, "secret")
+
+	def test_unreadable_marker_is_kept(self) -> None:
+		self.keyring.secrets.clear()
+		self.manager.reload()
+		self.assertEqual(self.manager.data.ACCOUNT_DATA.password, KEYRING_MARKER)
+		self.manager.update({"ACCOUNT_DATA": {"nickname": "猫"}})
+		self.assertEqual(self.saved_password(), KEYRING_MARKER)
+		with self.assertRaises(RuntimeError):
+			self.manager.migrate_credentials("file")
+
+	def test_missing_backend_falls_back_to_file(self) -> None:
+		CredentialVault.set_backend(None)
+		with patch("aumiao.utils.data.keyring", None):
+			self.manager.update({"ACCOUNT_DATA": {"password": "changed"}})
+			self.manager.update({"ACCOUNT_DATA": {"password": "changed again"}})
+			self.assertEqual(self.saved_password(), "changed again")
+			self.assertEqual(self.output.getvalue().count("没有可用的系统钥匙串"), 1)
+			with self.assertRaises(RuntimeError):
+				self.manager.migrate_credentials("keyring")
+
+	def test_migrate_between_stores(self) -> None:
+		self.manager.migrate_credentials("file")
+		self.assertEqual(self.saved_password(), "secret")
+		self.assertEqual(self.keyring.secrets, {})
+		self.assertEqual(SettingManager().data.PARAMETER.credential_store, "file")
+		self.manager.migrate_credentials("keyring")
+		self.assertEqual(self.saved_password(), KEYRING_MARKER)
+		self.assertEqual(self.keyring.secrets, {(KEYRING_SERVICE, "cat"): "secret"})
+		with self.assertRaises(ValueError):
+			self.manager.migrate_credentials(cast("CredentialStore", "vault"))
+
+
+class SettingValidatorTest(unittest.TestCase):
+	def test_unknown_credential_store(self) -> None:
+		setting = CodeMaoSetting()
+		setting.PARAMETER.credential_store = "vault"
+		self.assertEqual([error.path for error in SettingValidator.validate(setting)], ["PARAMETER.credential_store"])
+
+	def test_limits_only_need_to_be_non_negative(self) -> None:
+		setting = CodeMaoSetting()
+		setting.PARAMETER.report_work_max = 500
+		setting.PARAMETER.spam_del_max = -1
+		errors = SettingValidator.validate(setting)
+		self.assertEqual([error.path for error in errors], ["PARAMETER.spam_del_max"])
+
+	def test_headers_are_checked_after_env_interpolation(self) -> None:
+		setting = CodeMaoSetting()
+		setting.PROGRAM.HEADERS = {"X-Missing": "${AUMIAO_TEST_UNDEFINED}", "X-Line": "${AUMIAO_TEST_LINE}", "X-Token": "Bearer ${AUMIAO_TEST_TOKEN}"}
+		with patch.dict(environ, {"AUMIAO_TEST_LINE": "a\r\nb", "AUMIAO_TEST_TOKEN": "abc"}):
+			errors = SettingValidator.validate(setting)
+		self.assertEqual([error.path for error in errors], ["PROGRAM.HEADERS.X-Missing", "PROGRAM.HEADERS.X-Line"])
+		self.assertIn("AUMIAO_TEST_UNDEFINED", errors[0].message)
+
+
+if __name__ == "__main__":
+	unittest.main()
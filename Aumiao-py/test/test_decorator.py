@@ -0,0 +1,581 @@
+import unittest
+from concurrent.futures import ThreadPoolExecutor
+from contextlib import redirect_stdout
+from io import StringIO
+from random import Random
+from threading import Barrier, Event, Lock
+from time import monotonic, sleep
+from unittest.mock import patch
+
+from aumiao.utils.decorator import (
+	BatchRunner,
+	CallTimeoutError,
+	CircuitBreaker,
+	CircuitOpenError,
+	ConcurrencyLimiter,
+	Debouncer,
+	ErrorSink,
+	ProgressUpdate,
+	RateLimiter,
+	SingletonInitError,
+	Throttler,
+	TimingRegistry,
+	call_with_timeout,
+	memoize,
+	progress,
+	singleton,
+	singleton_fallible,
+	timed,
+	with_retry_and_timeout,
+	with_timeout,
+)
+
+
+class SingletonTest(unittest.TestCase):
+	def test_concurrent_first_access_creates_one_instance(self) -> None:
+		created: list[int] = []
+		barrier = Barrier(16)
+
+		@singleton
+		class Slow:
+			def __init__(self) -> None:
+				created.append(1)
+				sleep(0.01)
+
+		def access(_: int) -> object:
+			barrier.wait()
+			return Slow()
+
+		with ThreadPoolExecutor(max_workers=16) as executor:
+			instances = list(executor.map(access, range(16)))
+		self.assertEqual(len(created), 1)
+		self.assertTrue(all(instance is instances[0] for instance in instances))
+
+
+class SingletonFallibleTest(unittest.TestCase):
+	def test_failure_is_remembered_without_reconstructing(self) -> None:
+		attempts: list[int] = []
+
+		@singleton_fallible
+		class Broken:
+			def __init__(self) -> None:
+				attempts.append(1)
+				msg = "missing config"
+				raise OSError(msg)
+
+		self.assertIsNone(Broken.init_error())
+		with self.assertRaises(SingletonInitError) as first:
+			Broken()
+		with self.assertRaises(SingletonInitError) as second:
+			Broken()
+		self.assertEqual(len(attempts), 1)
+		self.assertIsInstance(Broken.init_error(), OSError)
+		self.assertIs(first.exception.__cause__, Broken.init_error())
+		self.assertIs(second.exception.__cause__, Broken.init_error())
+		self.assertIn("missing config", str(second.exception))
+
+	def test_successful_construction_is_shared(self) -> None:
+		@singleton_fallible
+		class Service:
+			def __init__(self, name: str) -> None:
+				self.name = name
+
+		self.assertIs(Service("a"), Service("b"))
+		self.assertEqual(Service().name, "a")
+		self.assertIsNone(Service.init_error())
+
+
+class RateLimiterTest(unittest.TestCase):
+	def test_extra_acquisition_waits_for_next_interval(self) -> None:
+		limiter = RateLimiter(3, 0.2)
+		start = monotonic()
+		for _ in range(4):
+			limiter.acquire()
+		self.assertGreaterEqual(monotonic() - start, 0.19)
+
+	def test_try_acquire(self) -> None:
+		limiter = RateLimiter(2, 10)
+		self.assertTrue(limiter.try_acquire())
+		self.assertTrue(limiter.try_acquire())
+		self.assertFalse(limiter.try_acquire())
+
+	def test_waiters_are_served_in_order(self) -> None:
+		limiter = RateLimiter(1, 0.05)
+		limiter.acquire()
+		order: list[int] = []
+
+		def worker(index: int) -> None:
+			sleep(index * 0.01)
+			limiter.acquire()
+			order.append(index)
+
+		with ThreadPoolExecutor(max_workers=4) as executor:
+			list(executor.map(worker, range(4)))
+		self.assertEqual(order, [0, 1, 2, 3])
+
+	def test_decorator(self) -> None:
+		calls: list[int] = []
+		limited = RateLimiter(5, 1)(calls.append)
+		for index in range(3):
+			limited(index)
+		self.assertEqual(calls, [0, 1, 2])
+
+
+class MemoizeTest(unittest.TestCase):
+	def test_concurrent_calls_run_once(self) -> None:
+		calls: list[int] = []
+		barrier = Barrier(8)
+
+		@memoize(ttl=10)
+		def slow(value: int) -> int:
+			calls.append(value)
+			sleep(0.05)
+			return value * 2
+
+		def call(_: int) -> int:
+			barrier.wait()
+			return slow(21)
+
+		with ThreadPoolExecutor(max_workers=8) as executor:
+			results = list(executor.map(call, range(8)))
+		self.assertEqual(results, [42] * 8)
+		self.assertEqual(calls, [21])
+
+	def test_ttl_expiry_and_invalidate(self) -> None:
+		calls: list[int] = []
+
+		@memoize(ttl=0.05)
+		def double(value: int) -> int:
+			calls.append(value)
+			return value * 2
+
+		double(1)
+		double(1)
+		self.assertEqual(len(calls), 1)
+		sleep(0.06)
+		double(1)
+		self.assertEqual(len(calls), 2)
+		double.invalidate(1)
+		double(1)
+		self.assertEqual(len(calls), 3)
+		double.clear()
+		double(1)
+		self.assertEqual(len(calls), 4)
+
+	def test_lru_capacity(self) -> None:
+		calls: list[int] = []
+
+		@memoize(ttl=10, capacity=2)
+		def identity(value: int) -> int:
+			calls.append(value)
+			return value
+
+		for value in (1, 2, 1, 3, 1, 2):
+			identity(value)
+		# 3 淘汰了最久未使用的 2, 1 一直保留
+		self.assertEqual(calls, [1, 2, 3, 2])
+
+	def test_errors_are_not_cached_by_default(self) -> None:
+		calls: list[int] = []
+
+		def failing(value: int) -> int:
+			calls.append(value)
+			msg = "boom"
+			raise ValueError(msg)
+
+		uncached = memoize(ttl=10)(failing)
+		for _ in range(2):
+			with self.assertRaises(ValueError):
+				uncached(1)
+		self.assertEqual(len(calls), 2)
+		cached = memoize(ttl=10, cache_errors=True)(failing)
+		for _ in range(2):
+			with self.assertRaises(ValueError):
+				cached(1)
+		self.assertEqual(len(calls), 3)
+
+
+class DebounceThrottleTest(unittest.TestCase):
+	def test_debouncer_runs_only_the_last_call(self) -> None:
+		calls: list[int] = []
+		with Debouncer(0.05) as debouncer:
+			for index in range(5):
+				debouncer.call(lambda index=index: calls.append(index))
+				sleep(0.01)
+			sleep(0.1)
+		self.assertEqual(calls, [4])
+
+	def test_debouncer_exit_while_pending_cancels(self) -> None:
+		calls: list[int] = []
+		with Debouncer(0.05) as debouncer:
+			debouncer.call(lambda: calls.append(1))
+		sleep(0.1)
+		self.assertEqual(calls, [])
+
+	def test_throttler_runs_leading_and_one_trailing_call(self) -> None:
+		calls: list[int] = []
+		with Throttler(0.1) as throttler:
+			for index in range(5):
+				throttler.call(lambda index=index: calls.append(index))
+			self.assertEqual(calls, [0])
+			sleep(0.15)
+			self.assertEqual(calls, [0, 4])
+
+	def test_throttler_exit_while_pending_cancels(self) -> None:
+		calls: list[int] = []
+		with Throttler(0.05) as throttler:
+			throttler.call(lambda: calls.append(1))
+			throttler.call(lambda: calls.append(2))
+		sleep(0.1)
+		self.assertEqual(calls, [1])
+
+
+class TimingRegistryTest(unittest.TestCase):
+	def setUp(self) -> None:
+		self.registry = TimingRegistry()
+		self._saved = (self.registry.clock, self.registry.max_rows)
+		self.ticks = iter([0.0, 1.0, 10.0, 13.0, 20.0, 22.0])
+		self.registry.clock = lambda: next(self.ticks)
+		self.registry.reset()
+
+	def tearDown(self) -> None:
+		self.registry.clock, self.registry.max_rows = self._saved
+		self.registry.reset()
+
+	def test_aggregation_with_injected_clock(self) -> None:
+		with self.registry.measure("a"):
+			pass
+		with self.assertRaises(ValueError), self.registry.measure("a"):
+			raise ValueError
+
+		@timed("b")
+		def work() -> int:
+			return 1
+
+		self.assertEqual(work(), 1)
+		rows = {row.name: row for row in self.registry.report()}
+		self.assertEqual((rows["a"].count, rows["a"].total, rows["a"].min, rows["a"].max, rows["a"].last), (2, 4.0, 1.0, 3.0, 3.0))
+		self.assertEqual((rows["b"].count, rows["b"].total), (1, 2.0))
+
+	def test_least_recently_recorded_names_are_evicted(self) -> None:
+		self.registry.max_rows = 2
+		for name in ("a", "b", "a", "c"):
+			self.registry.record(name, 1.0)
+		self.assertEqual([row.name for row in self.registry.report()], ["a", "c"])
+
+
+class BatchRunnerTest(unittest.TestCase):
+	def setUp(self) -> None:
+		self.sleeps: list[float] = []
+
+	def fail_on(self, bad: set[int]) -> object:
+		def func(item: int) -> int:
+			if item in bad:
+				msg = f"bad {item}"
+				raise ValueError(msg)
+			return item
+
+		return func
+
+	def test_delays_between_batches_and_report(self) -> None:
+		runner = BatchRunner(2, delay=1.0, concurrency=2, sleep_func=self.sleeps.append)
+		report = runner.run(range(5), self.fail_on({3}))
+		self.assertEqual(self.sleeps, [1.0, 1.0])
+		self.assertEqual(report.succeeded, 4)
+		self.assertEqual([(index, str(error)) for index, error in report.failed], [(3, "bad 3")])
+		self.assertFalse(report.cancelled)
+
+	def test_jitter_shortens_delay_within_range(self) -> None:
+		runner = BatchRunner(1, delay=2.0, jitter=0.5, sleep_func=self.sleeps.append, rng=Random(1))
+		runner.run(range(6), self.fail_on(set()))
+		self.assertEqual(len(self.sleeps), 5)
+		self.assertTrue(all(1.0 <= delay <= 2.0 for delay in self.sleeps))
+
+	def test_fail_fast_stops_after_failing_batch(self) -> None:
+		runner = BatchRunner(2, delay=0, fail_fast=True)
+		report = runner.run(range(6), self.fail_on({1}))
+		self.assertEqual((report.succeeded, len(report.failed)), (1, 1))
+
+	def test_cancel_stops_at_batch_boundary(self) -> None:
+		processed: list[int] = []
+		runner = BatchRunner(2, delay=0.5, sleep_func=self.sleeps.append)
+
+		def func(item: int) -> None:
+			processed.append(item)
+			if item == 1:
+				runner.cancel()
+
+		report = runner.run(range(6), func)
+		self.assertEqual(sorted(processed), [0, 1])
+		self.assertTrue(report.cancelled)
+		self.assertEqual(report.succeeded, 2)
+
+
+class CircuitBreakerTest(unittest.TestCase):
+	def setUp(self) -> None:
+		self.now = 0.0
+		self.breaker = CircuitBreaker(2, cooldown=10.0, clock=lambda: self.now)
+
+	def fail(self) -> None:
+		msg = "boom"
+		raise ValueError(msg)
+
+	def trip(self) -> None:
+		for _ in range(2):
+			with self.assertRaises(ValueError):
+				self.breaker.call(self.fail)
+
+	def test_opens_after_threshold_and_rejects_calls(self) -> None:
+		with self.assertRaises(ValueError):
+			self.breaker.call(self.fail)
+		self.assertEqual(self.breaker.state, "closed")
+		with self.assertRaises(ValueError):
+			self.breaker.call(self.fail)
+		self.assertEqual(self.breaker.state, "open")
+		self.now = 4.0
+		with self.assertRaises(CircuitOpenError) as ctx:
+			self.breaker.call(lambda: None)
+		self.assertAlmostEqual(ctx.exception.retry_in, 6.0)
+
+	def test_success_resets_failure_count(self) -> None:
+		with self.assertRaises(ValueError):
+			self.breaker.call(self.fail)
+		self.assertEqual(self.breaker.call(lambda: 1), 1)
+		with self.assertRaises(ValueError):
+			self.breaker.call(self.fail)
+		self.assertEqual(self.breaker.state, "closed")
+
+	def test_cooldown_expiry_allows_probe_that_closes(self) -> None:
+		self.trip()
+		self.now = 10.0
+		self.assertEqual(self.breaker.state, "half_open")
+		self.assertEqual(self.breaker.call(lambda: "ok"), "ok")
+		self.assertEqual(self.breaker.state, "closed")
+
+	def test_failed_probe_reopens_for_full_cooldown(self) -> None:
+		self.trip()
+		self.now = 10.0
+		with self.assertRaises(ValueError):
+			self.breaker.call(self.fail)
+		self.assertEqual(self.breaker.state, "open")
+		self.now = 19.0
+		self.assertEqual(self.breaker.state, "open")
+		self.now = 20.0
+		self.assertEqual(self.breaker.state, "half_open")
+
+	def test_only_one_probe_at_a_time(self) -> None:
+		self.trip()
+		self.now = 10.0
+
+		def probe() -> str:
+			with self.assertRaises(CircuitOpenError):
+				self.breaker.call(lambda: None)
+			return "done"
+
+		self.assertEqual(self.breaker.call(probe), "done")
+		self.assertEqual(self.breaker.state, "closed")
+
+	def test_base_exception_is_neutral(self) -> None:
+		def interrupt() -> None:
+			raise KeyboardInterrupt
+
+		for _ in range(3):
+			with self.assertRaises(KeyboardInterrupt):
+				self.breaker.call(interrupt)
+		self.assertEqual(self.breaker.state, "closed")
+		self.trip()
+		self.now = 10.0
+		with self.assertRaises(KeyboardInterrupt):
+			self.breaker.call(interrupt)
+		# 被中断的试探不计入失败, 仍可再次试探
+		self.assertEqual(self.breaker.state, "half_open")
+		self.assertIsNone(self.breaker.call(lambda: None))
+		self.assertEqual(self.breaker.state, "closed")
+
+	def test_decorator_form_shares_state(self) -> None:
+		calls: list[int] = []
+
+		@self.breaker
+		def flaky(value: int) -> int:
+			calls.append(value)
+			raise ValueError(value)
+
+		for value in range(2):
+			with self.assertRaises(ValueError):
+				flaky(value)
+		with self.assertRaises(CircuitOpenError):
+			flaky(2)
+		self.assertEqual(calls, [0, 1])
+
+
+class ProgressTest(unittest.TestCase):
+	def test_callbacks_are_throttled_and_final_update_is_reported(self) -> None:
+		updates: list[ProgressUpdate] = []
+		with patch("aumiao.utils.decorator.monotonic", side_effect=[0, 1, 2, 3, 4, 5, 6]):
+			items = list(progress(range(5), callback=updates.append, max_updates_per_second=0.5))
+		self.assertEqual(items, [0, 1, 2, 3, 4])
+		self.assertEqual([update.done for update in updates], [2, 4, 5])
+		self.assertEqual([update.elapsed for update in updates], [2, 4, 6])
+		self.assertEqual({update.total for update in updates}, {5})
+		self.assertEqual(updates[0].eta, 3)
+
+	def test_unthrottled_reports_each_item_once(self) -> None:
+		updates: list[ProgressUpdate] = []
+		list(progress(["a", "b", "c"], callback=updates.append, max_updates_per_second=0))
+		self.assertEqual([update.done for update in updates], [1, 2, 3])
+
+	def test_generator_without_total_has_no_eta(self) -> None:
+		updates: list[ProgressUpdate] = []
+		list(progress((item for item in range(3)), callback=updates.append, max_updates_per_second=0))
+		self.assertEqual(len(updates), 3)
+		self.assertTrue(all(update.total is None and update.eta is None for update in updates))
+
+	def test_empty_iterable_reports_once(self) -> None:
+		updates: list[ProgressUpdate] = []
+		self.assertEqual(list(progress([], callback=updates.append)), [])
+		self.assertEqual([(update.done, update.total) for update in updates], [(0, 0)])
+
+
+class ConcurrencyLimiterTest(unittest.TestCase):
+	def test_in_flight_never_exceeds_limit_under_load(self) -> None:
+		limiter = ConcurrencyLimiter(3)
+		lock = Lock()
+		active = 0
+		peak = 0
+		observed_gauge: list[int] = []
+
+		def task(index: int) -> int:
+			nonlocal active, peak
+			with lock:
+				active += 1
+				peak = max(peak, active)
+				observed_gauge.append(limiter.in_flight)
+			sleep(0.001)
+			with lock:
+				active -= 1
+			return index
+
+		with ThreadPoolExecutor(max_workers=20) as executor:
+			futures = [executor.submit(limiter.run, task, index) for index in range(100)]
+			results = [future.result() for future in futures]
+		self.assertEqual(results, list(range(100)))
+		self.assertLessEqual(peak, 3)
+		self.assertGreater(peak, 1)
+		self.assertTrue(all(gauge <= 3 for gauge in observed_gauge))
+		self.assertEqual((limiter.in_flight, limiter.queued), (0, 0))
+
+	def test_failures_release_the_permit(self) -> None:
+		limiter = ConcurrencyLimiter(2)
+
+		def task(index: int) -> int:
+			if index % 2:
+				msg = f"odd {index}"
+				raise ValueError(msg)
+			return index
+
+		results = limiter.map_concurrent(range(10), task)
+		self.assertEqual([result for result in results if not isinstance(result, Exception)], [0, 2, 4, 6, 8])
+		self.assertEqual(sum(isinstance(result, ValueError) for result in results), 5)
+		self.assertEqual(limiter.in_flight, 0)
+
+
+class ErrorSinkTest(unittest.TestCase):
+	def test_concurrent_records_are_not_lost(self) -> None:
+		sink = ErrorSink()
+		barrier = Barrier(8)
+
+		def worker(thread_index: int) -> None:
+			barrier.wait()
+			for index in range(50):
+				if index % 2:
+					sink.record(f"{thread_index}-{index}", KeyError(index))
+				else:
+					sink.call(f"{thread_index}-{index}", int, "not a number")
+
+		with redirect_stdout(StringIO()), ThreadPoolExecutor(max_workers=8) as executor:
+			for future in [executor.submit(worker, thread_index) for thread_index in range(8)]:
+				future.result()
+		self.assertEqual(len(sink), 400)
+		self.assertEqual(sink.summary(), {"KeyError": 200, "ValueError": 200})
+		self.assertEqual(len({record.context for record in sink.records()}), 400)
+
+	def test_call_returns_result_or_none(self) -> None:
+		sink = ErrorSink()
+		self.assertEqual(sink.call("ok", int, "3"), 3)
+		with redirect_stdout(StringIO()):
+			self.assertIsNone(sink.call("bad", int, "x"))
+		self.assertEqual([record.context for record in sink.records()], ["bad"])
+
+
+class TimeoutTest(unittest.TestCase):
+	def setUp(self) -> None:
+		# 模拟卡住的调用, 测试结束时放行, 避免遗留等待中的线程
+		self.release = Event()
+		self.addCleanup(self.release.set)
+
+	def hang_then_fail(self) -> None:
+		self.release.wait(5)
+		msg = "too late"
+		raise ValueError(msg)
+
+	def test_timeout_fires_before_error(self) -> None:
+		with self.assertRaises(CallTimeoutError) as context:
+			call_with_timeout(self.hang_then_fail, 0.05)
+		self.assertEqual(context.exception.timeout, 0.05)
+
+	def test_error_before_timeout_is_raised_unchanged(self) -> None:
+		def fail() -> None:
+			msg = "inner"
+			raise TimeoutError(msg)
+
+		with self.assertRaises(TimeoutError) as context:
+			call_with_timeout(fail, 5)
+		self.assertNotIsInstance(context.exception, CallTimeoutError)
+		self.assertEqual(str(context.exception), "inner")
+
+	def test_decorator_returns_result(self) -> None:
+		@with_timeout(5)
+		def add(a: int, b: int = 0) -> int:
+			return a + b
+
+		self.assertEqual(add(1, b=2), 3)
+
+	def test_timed_out_attempt_is_retried(self) -> None:
+		calls: list[int] = []
+		waits: list[float] = []
+
+		@with_retry_and_timeout(0.05, [0.5, 1.0], sleep_func=waits.append)
+		def flaky() -> str:
+			calls.append(1)
+			if len(calls) == 1:
+				self.hang_then_fail()
+			return "ok"
+
+		self.assertEqual(flaky(), "ok")
+		self.assertEqual((len(calls), waits), (2, [0.5]))
+
+	def test_every_attempt_timing_out_raises_timeout(self) -> None:
+		waits: list[float] = []
+		retrying = with_retry_and_timeout(0.02, [0.1, 0.2], sleep_func=waits.append)(self.hang_then_fail)
+		with self.assertRaises(CallTimeoutError):
+			retrying()
+		self.assertEqual(waits, [0.1, 0.2])
+
+	def test_inner_errors_follow_retry_if(self) -> None:
+		calls: list[int] = []
+
+		def fail() -> None:
+			calls.append(1)
+			msg = "bad input"
+			raise KeyError(msg)
+
+		with self.assertRaises(KeyError):
+			with_retry_and_timeout(5, [0, 0], retry_if=lambda e: not isinstance(e, KeyError), sleep_func=lambda _: None)(fail)()
+		self.assertEqual(len(calls), 1)
+		with self.assertRaises(KeyError):
+			with_retry_and_timeout(5, [0, 0], sleep_func=lambda _: None)(fail)()
+		self.assertEqual(len(calls), 4)
+
+
+if __name__ == "__main__":
+	unittest.main()
@@ -0,0 +1,75 @@
+import unittest
+
+from aumiao.utils.diff import PatchError, PatchOperation, apply_patch, generate_patch
+
+
+class GeneratePatchTest(unittest.TestCase):
+	def test_round_trip(self) -> None:
+		cases = [
+			({"a": 1, "b": {"c": [1, 2, 3]}}, {"a": 2, "b": {"c": [1, 3]}, "d": None}),
+			({"list": [1]}, {"list": [1, {"x": "y"}, 3]}),
+			({"a/b": 1, "m~n": 2}, {"a/b": 3}),
+			({"flag": 1}, {"flag": True}),
+			([1, 2], {"now": "object"}),
+			({}, {}),
+		]
+		for source, target in cases:
+			with self.subTest(source=source, target=target):
+				patch = generate_patch(source, target)
+				self.assertEqual(apply_patch(source, patch), target)
+				self.assertEqual(apply_patch(source, [PatchOperation.from_dict(operation.to_dict()) for operation in patch]), target)
+
+	def test_identical_documents_give_empty_patch(self) -> None:
+		self.assertEqual(generate_patch({"a": [1, {"b": 2}]}, {"a": [1, {"b": 2}]}), [])
+
+	def test_escaped_paths(self) -> None:
+		patch = generate_patch({"a/b": 1, "m~n": 2}, {"a/b": 1})
+		self.assertEqual([operation.to_dict() for operation in patch], [{"op": "remove", "path": "/m~0n"}])
+
+
+class ApplyPatchTest(unittest.TestCase):
+	def apply(self, doc: object, *operations: dict) -> object:
+		return apply_patch(doc, [PatchOperation.from_dict(operation) for operation in operations])
+
+	def test_rfc_examples(self) -> None:
+		self.assertEqual(self.apply({"foo": ["bar", "baz"]}, {"op": "add", "path": "/foo/1", "value": "qux"}), {"foo": ["bar", "qux", "baz"]})
+		self.assertEqual(self.apply({"foo": ["bar"]}, {"op": "add", "path": "/foo/-", "value": ["abc"]}), {"foo": ["bar", ["abc"]]})
+		self.assertEqual(self.apply({"baz": "qux", "foo": "bar"}, {"op": "replace", "path": "/baz", "value": "boo"}), {"baz": "boo", "foo": "bar"})
+		moved = self.apply({"foo": {"bar": "baz", "waldo": "fred"}, "qux": {"corge": "grault"}}, {"op": "move", "from": "/foo/waldo", "path": "/qux/thud"})
+		self.assertEqual(moved, {"foo": {"bar": "baz"}, "qux": {"corge": "grault", "thud": "fred"}})
+		self.assertEqual(self.apply({"foo": ["all", "grass", "cows", "eat"]}, {"op": "move", "from": "/foo/1", "path": "/foo/3"}), {"foo": ["all", "cows", "eat", "grass"]})
+		self.assertEqual(self.apply({"a": {"b": 1}}, {"op": "copy", "from": "/a", "path": "/c"}), {"a": {"b": 1}, "c": {"b": 1}})
+		self.assertEqual(self.apply({"q": {"bar": 2}}, {"op": "remove", "path": "/q/bar"}), {"q": {}})
+
+	def test_original_document_is_untouched_on_failure(self) -> None:
+		doc = {"a": [1, 2]}
+		with self.assertRaises(PatchError):
+			self.apply(doc, {"op": "remove", "path": "/a/0"}, {"op": "test", "path": "/a/0", "value": 1})
+		self.assertEqual(doc, {"a": [1, 2]})
+
+	def test_test_operation_uses_json_equality(self) -> None:
+		self.assertEqual(self.apply({"a": 1}, {"op": "test", "path": "/a", "value": 1}), {"a": 1})
+		with self.assertRaises(PatchError):
+			self.apply({"a": 1}, {"op": "test", "path": "/a", "value": True})
+
+	def test_invalid_operations(self) -> None:
+		cases = [
+			({"a": 1}, {"op": "remove", "path": "/missing"}),
+			({"a": [1]}, {"op": "add", "path": "/a/5", "value": 0}),
+			({"a": [1]}, {"op": "replace", "path": "/a/01", "value": 0}),
+			({"a": {"b": {}}}, {"op": "move", "from": "/a", "path": "/a/b/c"}),
+			({"a": 1}, {"op": "add", "path": "a", "value": 0}),
+			({"a": 1}, {"op": "remove", "path": ""}),
+		]
+		for doc, operation in cases:
+			with self.subTest(operation=operation), self.assertRaises(PatchError):
+				self.apply(doc, operation)
+
+	def test_malformed_operations_are_rejected(self) -> None:
+		for operation in ({"op": "rename", "path": "/a"}, {"op": "add", "path": "/a"}, {"op": "copy", "path": "/a"}, {"op": "remove"}):
+			with self.subTest(operation=operation), self.assertRaises(PatchError):
+				PatchOperation.from_dict(operation)
+
+
+if __name__ == "__main__":
+	unittest.main()
@@ -0,0 +1,62 @@
+import unittest
+from contextlib import redirect_stdout
+from copy import deepcopy
+from dataclasses import asdict
+from io import StringIO
+from json import loads
+from pathlib import Path
+from tempfile import TemporaryDirectory
+
+from aumiao.utils.data import DEFAULT_SETTING_DATA, CodeMaoSetting, DataClassConverter, JsonFileHandler
+from aumiao.utils.schema import DRAFT_07, setting_schema, validate, write_schema
+
+
+class SettingSchemaTest(unittest.TestCase):
+	def setUp(self) -> None:
+		self.schema = setting_schema()
+
+	def test_defaults_validate_against_schema(self) -> None:
+		self.assertEqual(validate(DEFAULT_SETTING_DATA, self.schema), [])
+		# 按默认值保存的 setting.json 包含所有字段, 同样应通过校验
+		saved = asdict(DataClassConverter.dict_to_dataclass(CodeMaoSetting, DEFAULT_SETTING_DATA))
+		self.assertEqual(validate(saved, self.schema), [])
+
+	def test_schema_metadata(self) -> None:
+		parameter = self.schema["properties"]["PARAMETER"]["properties"]
+		self.assertEqual(self.schema["$schema"], DRAFT_07)
+		self.assertEqual(parameter["password_login_method"]["default"], "token")
+		self.assertEqual(parameter["password_login_method"]["enum"], ["", "password_v0", "password_v1", "password_v2", "token"])
+		self.assertEqual(parameter["credential_store"]["enum"], ["file", "keyring"])
+		self.assertEqual(parameter["all_read_type"]["items"]["enum"], ["COMMENT_REPLY", "LIKE_FORK", "SYSTEM"])
+		self.assertIn("keyring", parameter["credential_store"]["description"])
+		self.assertNotIn("description", self.schema["properties"]["PROGRAM"])
+
+	def test_invalid_settings_are_reported(self) -> None:
+		cases = [
+			("password_login_method", "password_v9", "$.PARAMETER.password_login_method"),
+			("spam_del_max", -1, "$.PARAMETER.spam_del_max"),
+			("safe_mode", 1, "$.PARAMETER.safe_mode"),
+			("all_read_type", ["SYSTEM", "OTHER"], "$.PARAMETER.all_read_type[1]"),
+			("endpoint_log_overrides", {"/web": "verbose"}, "$.PARAMETER.endpoint_log_overrides./web"),
+			("spam_dell_max", 3, "$.PARAMETER.spam_dell_max"),
+		]
+		for name, value, path in cases:
+			with self.subTest(name=name):
+				setting = deepcopy(DEFAULT_SETTING_DATA)
+				setting["PARAMETER"][name] = value
+				errors = validate(setting, self.schema)
+				self.assertEqual(len(errors), 1, errors)
+				self.assertTrue(errors[0].startswith(f"{path}: "), errors)
+
+	def test_write_schema(self) -> None:
+		with TemporaryDirectory() as tmp, redirect_stdout(StringIO()):
+			path = Path(tmp) / "setting.schema.json"
+			write_schema(path)
+			self.assertEqual(loads(path.read_text(encoding="utf-8")), self.schema)
+			setting_path = Path(tmp) / "setting.json"
+			JsonFileHandler.save_json_file(setting_path, DataClassConverter.dict_to_dataclass(CodeMaoSetting, DEFAULT_SETTING_DATA))
+			self.assertEqual(validate(loads(setting_path.read_text(encoding="utf-8")), loads(path.read_text(encoding="utf-8"))), [])
+
+
+if __name__ == "__main__":
+	unittest.main()
@@ -0,0 +1,213 @@
+import unittest
+from datetime import UTC, datetime
+from zoneinfo import ZoneInfo
+
+from aumiao.utils.data import CommentTemplate
+from aumiao.utils.tool import CharCategoryCount, DataConverter, DataProcessor, EduDataGenerator, FieldFilterStrategy, StringProcessor, TimeUtils
+
+
+class CookieTest(unittest.TestCase):
+	def test_value_with_separators_round_trips(self) -> None:
+		cookie = {"aliyungf_tc": "a=b=c; d", "token": "plain"}
+		encoded = DataConverter.convert_cookie(cookie)
+		self.assertNotIn("; d", encoded)
+		self.assertEqual(DataConverter.parse_cookie_string(encoded), cookie)
+
+	def test_key_is_not_encoded(self) -> None:
+		self.assertEqual(DataConverter.convert_cookie({"a_b": "1"}), "a_b=1")
+
+
+class HtmlToTextTest(unittest.TestCase):
+	def test_image_with_alt_uses_src_by_default(self) -> None:
+		self.assertEqual(DataConverter.html_to_text('<img src="x.png" alt="cat">'), "[图片: x.png]")
+
+	def test_alt_placeholder(self) -> None:
+		html = '看<img alt="cat" src="x.png"><img src="y.png">'
+		self.assertEqual(DataConverter.html_to_text(html, img_format="[{alt}|{src}]"), "看[cat|x.png][|y.png]")
+
+
+class TemplateRenderTest(unittest.TestCase):
+	def test_render_string_and_template(self) -> None:
+		self.assertEqual(DataConverter.template_render("I'm {nickname}", {"nickname": "猫"}), "I'm 猫")
+		self.assertEqual(DataConverter.template_render(CommentTemplate(text="QQ {qq_number}"), {"qq_number": "1"}), "QQ 1")
+
+	def test_missing_or_malformed_placeholders_are_kept(self) -> None:
+		for text in ("{missing}", "{unclosed", "{0}"):
+			with self.subTest(text=text):
+				self.assertEqual(DataConverter.template_render(text, {}), text)
+
+
+class JsonToTableTest(unittest.TestCase):
+	def test_ascii_padding_and_cjk_alignment(self) -> None:
+		table = DataConverter.json_to_table([{"name": "编程猫", "id": 1}, {"name": "cat", "id": 22}])
+		self.assertEqual(
+			table.splitlines(),
+			[
+				"+------+----------+",
+				"|  id  |  name    |",
+				"+------+----------+",
+				"|  1   |  编程猫  |",
+				"|  22  |  cat     |",
+				"+------+----------+",
+			],
+		)
+
+	def test_markdown_and_truncation(self) -> None:
+		table = DataConverter.json_to_table([{"text": "猫" * 10}], style="markdown", max_cell_width=8)
+		self.assertEqual(table.splitlines(), ["|  text     |", "|-----------|", "|  猫猫...  |"])
+
+
+class CountByTest(unittest.TestCase):
+	DATA = [{"user": {"name": "b"}}, {"user": {"name": "a"}}, {"user": {"name": "a"}}, {"user": {}}, {"user": {"name": 1}}]
+
+	def test_first_seen_order_by_default(self) -> None:
+		result = DataProcessor.count_by(self.DATA, "user.name")
+		self.assertEqual(list(result.counts.items()), [("b", 1), ("a", 2), ("__missing__", 1), ("1", 1)])
+		self.assertEqual(result.total, 5)
+
+	def test_sorted_by_count(self) -> None:
+		result = DataProcessor.count_by(self.DATA, "user.name", sort=True)
+		self.assertEqual(list(result.counts), ["a", "1", "__missing__", "b"])
+
+
+class FilterFieldsRecursiveTest(unittest.TestCase):
+	DATA = {
+		"id": 1,
+		"user": {"name": "a", "profile": {"bio": "b", "avatar": {"url": "u", "size": 2}}, "token": "t"},
+		"items": [{"id": 2, "secret": "s"}],
+	}
+
+	def test_included_path_keeps_whole_subtree(self) -> None:
+		result = DataProcessor.filter_fields_recursive(self.DATA, include=["user.profile"])
+		self.assertEqual(result, {"user": {"profile": {"bio": "b", "avatar": {"url": "u", "size": 2}}}})
+
+	def test_bare_key_is_excluded_at_every_level(self) -> None:
+		result = DataProcessor.filter_fields_recursive(self.DATA, exclude=["id", "user.token"])
+		self.assertEqual(result, {"user": {"name": "a", "profile": self.DATA["user"]["profile"]}, "items": [{"secret": "s"}]})
+
+	def test_depth_limit(self) -> None:
+		result = DataProcessor.filter_fields_recursive(self.DATA, exclude=["url", "secret"], max_depth=1)
+		self.assertEqual(result["user"]["profile"]["avatar"], {"url": "u", "size": 2})
+		self.assertEqual(result["items"], [{"id": 2}])
+
+	def test_include_exclude_and_depth_together(self) -> None:
+		result = FieldFilterStrategy.filter_recursive(self.DATA, {"user.profile", "id"}, {"bio", "size"}, 2)
+		self.assertEqual(result, {"id": 1, "user": {"profile": {"avatar": {"url": "u", "size": 2}}}})
+		result = FieldFilterStrategy.filter_recursive(self.DATA, {"user.profile"}, {"bio", "size"}, None)
+		self.assertEqual(result, {"user": {"profile": {"avatar": {"url": "u"}}}})
+
+
+class ChunkedTest(unittest.TestCase):
+	def test_exact_multiple(self) -> None:
+		self.assertEqual(list(DataProcessor.chunked(range(6), 3)), [[0, 1, 2], [3, 4, 5]])
+
+	def test_remainder_is_yielded(self) -> None:
+		self.assertEqual(list(DataProcessor.chunked((item for item in range(7)), 3)), [[0, 1, 2], [3, 4, 5], [6]])
+
+	def test_empty_input(self) -> None:
+		self.assertEqual(list(DataProcessor.chunked([], 3)), [])
+
+	def test_size_must_be_positive(self) -> None:
+		with self.assertRaises(ValueError):
+			list(DataProcessor.chunked([1], 0))
+
+
+class DeepMergeTest(unittest.TestCase):
+	def test_override_precedence(self) -> None:
+		base = {"a": {"b": 1, "c": [1, 2]}, "d": "base", "e": {"f": 1}}
+		override = {"a": {"c": [3]}, "d": {"now": "dict"}, "e": 2, "g": None}
+		merged = DataProcessor.deep_merge(base, override)
+		self.assertEqual(merged, {"a": {"b": 1, "c": [3]}, "d": {"now": "dict"}, "e": 2, "g": None})
+		# 输入不会被修改
+		self.assertEqual(base, {"a": {"b": 1, "c": [1, 2]}, "d": "base", "e": {"f": 1}})
+
+	def test_empty_override_returns_copy(self) -> None:
+		base = {"a": 1}
+		merged = DataProcessor.deep_merge(base, {})
+		self.assertEqual(merged, base)
+		self.assertIsNot(merged, base)
+
+
+class NormalizeInputTest(unittest.TestCase):
+	def test_empty_envelope_is_reported_as_warning(self) -> None:
+		result, warnings = DataProcessor.filter_by_nested_values_with_warnings({"items": [], "total": 0}, "id", [1])
+		self.assertEqual(result, [])
+		self.assertEqual([warning.code for warning in warnings], ["empty_items_envelope"])
+
+	def test_no_warning_when_filter_yields_nothing(self) -> None:
+		result, warnings = DataProcessor.filter_by_nested_values_with_warnings({"items": [{"id": 2}]}, "id", [1])
+		self.assertEqual((result, warnings), ([], []))
+		self.assertEqual(DataProcessor.filter_by_nested_values({"items": [{"id": 1}]}, "id", [1]), [{"id": 1}])
+
+	def test_default_path_logs_warning(self) -> None:
+		with self.assertLogs("aumiao.utils.tool", level="WARNING") as logs:
+			self.assertEqual(DataProcessor.filter_by_nested_values({"items": [], "total": 0}, "id", [1]), [])
+		self.assertEqual(len(logs.records), 1)
+		self.assertIn("empty_items_envelope", logs.output[0])
+
+	def test_with_warnings_path_does_not_log(self) -> None:
+		with self.assertNoLogs("aumiao.utils.tool", level="WARNING"):
+			DataProcessor.filter_by_nested_values_with_warnings({"items": []}, "id", [1])
+
+
+class CharCategoryTest(unittest.TestCase):
+	def test_mixed_chinese_and_english(self) -> None:
+		text = "编程猫 Aumiao, 你好！"
+		self.assertEqual(StringProcessor.count_chars_by_category(text), CharCategoryCount(ascii=6, cjk=5, other=0, punctuation=2, total=15, whitespace=2))
+		self.assertAlmostEqual(StringProcessor.cjk_ratio(text), 5 / 15)
+
+	def test_pure_emoji(self) -> None:
+		self.assertEqual(StringProcessor.count_chars_by_category("😀🐱"), CharCategoryCount(other=2, total=2))
+		self.assertEqual(StringProcessor.cjk_ratio("😀🐱"), 0.0)
+
+	def test_empty_text(self) -> None:
+		self.assertEqual(StringProcessor.cjk_ratio(""), 0.0)
+
+
+class TimeBoundaryTest(unittest.TestCase):
+	zone = ZoneInfo("America/New_York")
+
+	def test_day_boundary_across_dst_changes(self) -> None:
+		# 2025-03-09 夏令时开始, 当天只有 23 小时; 2025-11-02 结束, 当天有 25 小时
+		self.assertEqual(TimeUtils.seconds_until_next_boundary("day", datetime(2025, 3, 9, tzinfo=self.zone)), 23 * 3600)
+		self.assertEqual(TimeUtils.seconds_until_next_boundary("day", datetime(2025, 11, 2, tzinfo=self.zone)), 25 * 3600)
+		self.assertEqual(TimeUtils.seconds_until_next_boundary("week", datetime(2025, 3, 9, tzinfo=self.zone)), 23 * 3600)
+
+	def test_hour_boundary_during_repeated_hour(self) -> None:
+		for fold in (0, 1):
+			with self.subTest(fold=fold):
+				now = datetime(2025, 11, 2, 1, 30, tzinfo=self.zone, fold=fold)
+				self.assertEqual(TimeUtils.seconds_until_next_boundary("hour", now), 1800)
+
+	def test_named_time_zone(self) -> None:
+		now = datetime(2025, 3, 9, 5, tzinfo=UTC)
+		self.assertEqual(TimeUtils.seconds_until_next_boundary("day", now, tz="America/New_York"), 23 * 3600)
+		self.assertEqual(TimeUtils.seconds_until_next_boundary("day", now, tz="Asia/Shanghai"), 11 * 3600)
+
+	def test_custom_period_and_invalid_boundary(self) -> None:
+		self.assertEqual(TimeUtils.seconds_until_next_boundary(60, datetime(2025, 1, 1, 0, 0, 10, tzinfo=UTC)), 50)
+		with self.assertRaises(ValueError):
+			TimeUtils.seconds_until_next_boundary(0)
+
+
+class EduDataGeneratorTest(unittest.TestCase):
+	def test_same_seed_gives_same_names(self) -> None:
+		generator = EduDataGenerator()
+		first = generator.generate_student_names(20, seed=42)
+		self.assertEqual(generator.generate_student_names(20, seed=42), first)
+		self.assertNotEqual(generator.generate_student_names(20, seed=43), first)
+
+	def test_seeded_gender_is_respected(self) -> None:
+		names = EduDataGenerator().generate_student_names(50, "female", seed=7)
+		female_names = EduDataGenerator._FEMALE_NAMES
+		self.assertTrue(all(any(name[1:].startswith(first) for first in female_names) for name in names))
+
+	def test_seeded_class_names(self) -> None:
+		generator = EduDataGenerator()
+		first = generator.generate_class_names(10, (1, 3), use_letters=True, add_specialty=True, seed=1)
+		self.assertEqual(generator.generate_class_names(10, (1, 3), use_letters=True, add_specialty=True, seed=1), first)
+		self.assertTrue(all(name.split(" ")[0] in {"一", "二", "三"} for name in first))
+
+
+if __name__ == "__main__":
+	unittest.main()